@@ -6,7 +6,7 @@ extern crate regex;
 
 use std::env;
 use std::fs::{self, create_dir, symlink_metadata, File};
-use std::io::{self, BufRead, BufReader, Write};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::PathBuf;
 use std::process::Command;
 use std::str;
@@ -454,6 +454,118 @@ fn search_include(include_paths: &Vec<PathBuf>, header: &str) -> String {
     format!("/usr/include/{}", header)
 }
 
+fn generate_pixfmt_aliases(include_paths: &Vec<PathBuf>) {
+    let mut header = String::new();
+    File::open(search_include(include_paths, "libavutil/pixfmt.h"))
+        .and_then(|mut file| file.read_to_string(&mut header))
+        .expect("Failed to read libavutil/pixfmt.h");
+
+    // #define AV_PIX_FMT_GRAY16 AV_PIX_FMT_NE(GRAY16BE, GRAY16LE)
+    let alias = Regex::new(
+        r"#\s*define\s+AV_PIX_FMT_(\w+)\s+AV_PIX_FMT_NE\(\s*(\w+)\s*,\s*(\w+)\s*\)",
+    ).unwrap();
+    // AV_PIX_FMT_GRAY16BE, ///< ...
+    let variant = Regex::new(r"(?m)^\s*AV_PIX_FMT_(\w+)(BE|LE)\s*[,=]").unwrap();
+
+    let mut aliases: Vec<(String, String, String)> = Vec::new();
+    for cap in alias.captures_iter(&header) {
+        if aliases.iter().all(|&(ref name, _, _)| name != &cap[1]) {
+            aliases.push((cap[1].to_string(), cap[2].to_string(), cap[3].to_string()));
+        }
+    }
+
+    // BE/LE pairs in the enum, each expected to have a native-endian alias
+    let mut variants: Vec<(String, String)> = Vec::new();
+    for cap in variant.captures_iter(&header) {
+        variants.push((cap[1].to_string(), cap[2].to_string()));
+    }
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    for &(ref stem, _) in &variants {
+        let be = format!("{}BE", stem);
+        let le = format!("{}LE", stem);
+
+        if variants.iter().any(|&(ref s, ref e)| s == stem && e == "BE")
+            && variants.iter().any(|&(ref s, ref e)| s == stem && e == "LE")
+            && !pairs.iter().any(|&(ref b, _)| b == &be)
+        {
+            pairs.push((be, le));
+        }
+    }
+
+    let mut f = File::create(output().join("pixfmt.rs")).expect("Failed to create pixfmt.rs");
+    for &(ref name, ref be, ref le) in &aliases {
+        writeln!(
+            f,
+            r#"#[cfg(target_endian = "little")]
+pub const AV_PIX_FMT_{name}: AVPixelFormat = AV_PIX_FMT_{le};
+#[cfg(target_endian = "big")]
+pub const AV_PIX_FMT_{name}: AVPixelFormat = AV_PIX_FMT_{be};
+"#,
+            name = name,
+            be = be,
+            le = le
+        ).expect("Write failed");
+    }
+
+    // Tables for the test that every pair has an alias
+    writeln!(f, "#[cfg(test)]\nconst ENDIAN_PAIRS: &[(AVPixelFormat, AVPixelFormat)] = &[")
+        .expect("Write failed");
+    for &(ref be, ref le) in &pairs {
+        writeln!(f, "    (AV_PIX_FMT_{}, AV_PIX_FMT_{}),", be, le).expect("Write failed");
+    }
+    writeln!(f, "];\n").expect("Write failed");
+
+    writeln!(
+        f,
+        "#[cfg(test)]\nconst NE_ALIASES: &[(AVPixelFormat, AVPixelFormat, AVPixelFormat)] = &["
+    ).expect("Write failed");
+    for &(ref name, ref be, ref le) in &aliases {
+        writeln!(
+            f,
+            "    (AV_PIX_FMT_{}, AV_PIX_FMT_{}, AV_PIX_FMT_{}),",
+            name, be, le
+        ).expect("Write failed");
+    }
+    writeln!(f, "];").expect("Write failed");
+}
+
+fn build_log_bridge(include_paths: &Vec<PathBuf>) {
+    let source = output().join("log_bridge.c");
+
+    write!(
+        File::create(&source).expect("Failed to create file"),
+        r#"
+            #include <libavutil/log.h>
+
+            static void (*line_callback)(void *, int, const char *);
+
+            static void callback(void *avcl, int level, const char *fmt, va_list vl)
+            {{
+                char line[1024];
+                int print_prefix = 0;
+
+                if (level > av_log_get_level())
+                    return;
+
+                av_log_format_line2(avcl, level, fmt, vl, line, sizeof(line), &print_prefix);
+                line_callback(avcl, level, line);
+            }}
+
+            void ffmpeg_sys_log_set_callback(void (*cb)(void *, int, const char *))
+            {{
+                line_callback = cb;
+                av_log_set_callback(cb ? callback : av_log_default_callback);
+            }}
+        "#
+    ).expect("Write failed");
+
+    let mut build = cc::Build::new();
+    for dir in include_paths {
+        build.include(dir);
+    }
+    build.file(&source).compile("ffmpeg_sys_log_bridge");
+}
+
 fn link_to_libraries(statik: bool) {
     let ffmpeg_ty = if statik { "static" } else { "dylib" };
     for lib in LIBRARIES {
@@ -1021,4 +1133,12 @@ fn main() {
     bindings
         .write_to_file(output().join("bindings.rs"))
         .expect("Couldn't write bindings!");
+
+    // AV_PIX_FMT_NE aliases are function-like macros bindgen can't see through.
+    generate_pixfmt_aliases(&include_paths);
+
+    // the av_log callback receives a va_list, which is formatted on the C side
+    if env::var("CARGO_FEATURE_LOG").is_ok() || env::var("CARGO_FEATURE_TRACING").is_ok() {
+        build_log_bridge(&include_paths);
+    }
 }
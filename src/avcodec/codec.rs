@@ -0,0 +1,280 @@
+use std::fmt;
+use std::ptr;
+use std::slice;
+
+use libc::{c_int, c_void};
+use {av_codec_is_decoder, av_codec_is_encoder, av_codec_iterate, avcodec_find_decoder,
+     avcodec_find_decoder_by_name, avcodec_find_encoder, avcodec_find_encoder_by_name,
+     avcodec_get_hw_config, cstring, ptr_to_str, AVCodec, AVCodecID, AVHWDeviceType, AVMediaType,
+     AVPixelFormat, AVProfile, AVRational, ChannelLayout, SampleFormat, FF_PROFILE_UNKNOWN};
+use {AV_CODEC_CAP_AUTO_THREADS, AV_CODEC_CAP_AVOID_PROBING, AV_CODEC_CAP_CHANNEL_CONF,
+     AV_CODEC_CAP_DELAY, AV_CODEC_CAP_DR1, AV_CODEC_CAP_DRAW_HORIZ_BAND,
+     AV_CODEC_CAP_EXPERIMENTAL, AV_CODEC_CAP_FRAME_THREADS, AV_CODEC_CAP_INTRA_ONLY,
+     AV_CODEC_CAP_LOSSLESS, AV_CODEC_CAP_PARAM_CHANGE, AV_CODEC_CAP_SLICE_THREADS,
+     AV_CODEC_CAP_SMALL_LAST_FRAME, AV_CODEC_CAP_SUBFRAMES, AV_CODEC_CAP_TRUNCATED,
+     AV_CODEC_CAP_VARIABLE_FRAME_SIZE};
+use AVPixelFormat::AV_PIX_FMT_NONE;
+use AVSampleFormat::AV_SAMPLE_FMT_NONE;
+
+bitflags! {
+    pub struct CodecCapabilities: u32 {
+        const DRAW_HORIZ_BAND     = AV_CODEC_CAP_DRAW_HORIZ_BAND;
+        /// Supports custom allocators through `get_buffer2`.
+        const DR1                 = AV_CODEC_CAP_DR1;
+        const TRUNCATED           = AV_CODEC_CAP_TRUNCATED;
+        /// Output is delayed, so the codec must be drained at the end.
+        const DELAY               = AV_CODEC_CAP_DELAY;
+        const SMALL_LAST_FRAME    = AV_CODEC_CAP_SMALL_LAST_FRAME;
+        const SUBFRAMES           = AV_CODEC_CAP_SUBFRAMES;
+        const EXPERIMENTAL        = AV_CODEC_CAP_EXPERIMENTAL;
+        const CHANNEL_CONF        = AV_CODEC_CAP_CHANNEL_CONF;
+        const FRAME_THREADS       = AV_CODEC_CAP_FRAME_THREADS;
+        const SLICE_THREADS       = AV_CODEC_CAP_SLICE_THREADS;
+        const PARAM_CHANGE        = AV_CODEC_CAP_PARAM_CHANGE;
+        const AUTO_THREADS        = AV_CODEC_CAP_AUTO_THREADS;
+        /// Audio encoder accepting frames of any size.
+        const VARIABLE_FRAME_SIZE = AV_CODEC_CAP_VARIABLE_FRAME_SIZE;
+        const AVOID_PROBING       = AV_CODEC_CAP_AVOID_PROBING;
+        const INTRA_ONLY          = AV_CODEC_CAP_INTRA_ONLY;
+        const LOSSLESS            = AV_CODEC_CAP_LOSSLESS;
+    }
+}
+
+bitflags! {
+    /// How a codec can be set up for a hardware configuration.
+    ///
+    /// The `AV_CODEC_HW_CONFIG_METHOD_*` values are an anonymous enum, which
+    /// bindgen does not expose as constants.
+    pub struct HwConfigMethods: c_int {
+        const HW_DEVICE_CTX = 0x01;
+        const HW_FRAMES_CTX = 0x02;
+        const INTERNAL      = 0x04;
+        const AD_HOC        = 0x08;
+    }
+}
+
+/// A decoder or encoder registered with libavcodec.
+///
+/// Codecs are static, so handles can be copied and kept freely.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Codec {
+    ptr: *const AVCodec,
+}
+
+unsafe impl Send for Codec {}
+unsafe impl Sync for Codec {}
+
+impl Codec {
+    pub unsafe fn wrap(ptr: *const AVCodec) -> Self {
+        Codec { ptr }
+    }
+
+    pub fn find_decoder(id: AVCodecID) -> Option<Codec> {
+        unsafe { Codec::from_lookup(avcodec_find_decoder(id)) }
+    }
+
+    pub fn find_decoder_by_name(name: &str) -> Option<Codec> {
+        let name = cstring(name).ok()?;
+        unsafe { Codec::from_lookup(avcodec_find_decoder_by_name(name.as_ptr())) }
+    }
+
+    pub fn find_encoder(id: AVCodecID) -> Option<Codec> {
+        unsafe { Codec::from_lookup(avcodec_find_encoder(id)) }
+    }
+
+    pub fn find_encoder_by_name(name: &str) -> Option<Codec> {
+        let name = cstring(name).ok()?;
+        unsafe { Codec::from_lookup(avcodec_find_encoder_by_name(name.as_ptr())) }
+    }
+
+    /// Every registered decoder and encoder.
+    pub fn all() -> CodecIter {
+        CodecIter {
+            opaque: ptr::null_mut(),
+        }
+    }
+
+    unsafe fn from_lookup(ptr: *const AVCodec) -> Option<Codec> {
+        if ptr.is_null() {
+            None
+        } else {
+            Some(Codec { ptr })
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const AVCodec {
+        self.ptr
+    }
+
+    /// Short name, e.g. `"h264"` or `"libx264"`.
+    pub fn name(&self) -> &'static str {
+        unsafe { ptr_to_str((*self.ptr).name).unwrap_or("") }
+    }
+
+    pub fn long_name(&self) -> Option<&'static str> {
+        unsafe { ptr_to_str((*self.ptr).long_name) }
+    }
+
+    pub fn media_type(&self) -> AVMediaType {
+        unsafe { (*self.ptr).type_ }
+    }
+
+    pub fn id(&self) -> AVCodecID {
+        unsafe { (*self.ptr).id }
+    }
+
+    pub fn is_decoder(&self) -> bool {
+        unsafe { av_codec_is_decoder(self.ptr) != 0 }
+    }
+
+    pub fn is_encoder(&self) -> bool {
+        unsafe { av_codec_is_encoder(self.ptr) != 0 }
+    }
+
+    pub fn capabilities(&self) -> CodecCapabilities {
+        CodecCapabilities::from_bits_truncate(unsafe { (*self.ptr).capabilities } as u32)
+    }
+
+    /// Supported frame rates, `None` if any.
+    pub fn frame_rates(&self) -> Option<&'static [AVRational]> {
+        unsafe {
+            terminated((*self.ptr).supported_framerates, |rate| rate.num == 0 && rate.den == 0)
+        }
+    }
+
+    /// Supported pixel formats, `None` if unknown.
+    pub fn pixel_formats(&self) -> Option<&'static [AVPixelFormat]> {
+        unsafe { terminated((*self.ptr).pix_fmts, |&format| format == AV_PIX_FMT_NONE) }
+    }
+
+    /// Supported sample rates, `None` if any.
+    pub fn sample_rates(&self) -> Option<&'static [c_int]> {
+        unsafe { terminated((*self.ptr).supported_samplerates, |&rate| rate == 0) }
+    }
+
+    /// Supported sample formats, `None` if unknown.
+    pub fn sample_formats(&self) -> Option<Vec<SampleFormat>> {
+        let formats = unsafe {
+            terminated((*self.ptr).sample_fmts, |&format| format == AV_SAMPLE_FMT_NONE)?
+        };
+
+        Some(formats.iter().map(|&format| SampleFormat(format)).collect())
+    }
+
+    /// Supported channel layouts, `None` if unknown.
+    pub fn channel_layouts(&self) -> Option<Vec<ChannelLayout>> {
+        let layouts = unsafe { terminated((*self.ptr).channel_layouts, |&layout| layout == 0)? };
+
+        Some(layouts.iter().map(|&layout| ChannelLayout::from_bits_truncate(layout)).collect())
+    }
+
+    /// Recognized profiles as `FF_PROFILE_*` values and names.
+    pub fn profiles(&self) -> Profiles {
+        Profiles {
+            ptr: unsafe { (*self.ptr).profiles },
+        }
+    }
+
+    /// Hardware acceleration setups the codec supports.
+    pub fn hw_configs(&self) -> HwConfigs {
+        HwConfigs {
+            codec: self.ptr,
+            index: 0,
+        }
+    }
+}
+
+impl fmt::Debug for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Codec")
+            .field("name", &self.name())
+            .field("id", &self.id())
+            .field("decoder", &self.is_decoder())
+            .finish()
+    }
+}
+
+/// Borrows a list ending in a sentinel, `None` if the list is null.
+unsafe fn terminated<T, F>(ptr: *const T, end: F) -> Option<&'static [T]>
+where
+    F: Fn(&T) -> bool,
+{
+    if ptr.is_null() {
+        return None;
+    }
+
+    let mut len = 0;
+    while !end(&*ptr.add(len)) {
+        len += 1;
+    }
+
+    Some(slice::from_raw_parts(ptr, len))
+}
+
+pub struct CodecIter {
+    opaque: *mut c_void,
+}
+
+impl Iterator for CodecIter {
+    type Item = Codec;
+
+    fn next(&mut self) -> Option<Codec> {
+        unsafe { Codec::from_lookup(av_codec_iterate(&mut self.opaque)) }
+    }
+}
+
+pub struct Profiles {
+    ptr: *const AVProfile,
+}
+
+impl Iterator for Profiles {
+    type Item = (c_int, &'static str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            if self.ptr.is_null() || (*self.ptr).profile == FF_PROFILE_UNKNOWN {
+                return None;
+            }
+
+            let profile = &*self.ptr;
+            self.ptr = self.ptr.add(1);
+
+            Some((profile.profile, ptr_to_str(profile.name).unwrap_or("")))
+        }
+    }
+}
+
+/// A pixel format a codec can output or take in hardware surfaces, and how
+/// to set that up.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct HwConfig {
+    pub pixel_format: AVPixelFormat,
+    pub methods: HwConfigMethods,
+    /// The device type for the `HW_DEVICE_CTX` and `HW_FRAMES_CTX` methods.
+    pub device_type: AVHWDeviceType,
+}
+
+pub struct HwConfigs {
+    codec: *const AVCodec,
+    index: c_int,
+}
+
+impl Iterator for HwConfigs {
+    type Item = HwConfig;
+
+    fn next(&mut self) -> Option<HwConfig> {
+        let config = unsafe { avcodec_get_hw_config(self.codec, self.index) };
+        if config.is_null() {
+            return None;
+        }
+        self.index += 1;
+
+        unsafe {
+            Some(HwConfig {
+                pixel_format: (*config).pix_fmt,
+                methods: HwConfigMethods::from_bits_truncate((*config).methods),
+                device_type: (*config).device_type,
+            })
+        }
+    }
+}
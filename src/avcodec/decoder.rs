@@ -0,0 +1,299 @@
+use std::collections::VecDeque;
+use std::ptr;
+
+use libc::{c_void, EAGAIN, EINVAL, ENOMEM};
+use {avcodec_alloc_context3, avcodec_flush_buffers, avcodec_free_context, avcodec_is_open,
+     avcodec_open2, avcodec_parameters_to_context, avcodec_receive_frame, avcodec_send_packet,
+     AVCodecContext, AVCodecParameters, AVPixelFormat, AVRational, AvError, ChannelLayout, Codec,
+     Dictionary, Frame, Options, Packet, SampleFormat, AVERROR, AVERROR_DECODER_NOT_FOUND,
+     AVERROR_EOF};
+
+/// A decoder, owning its `AVCodecContext`.
+///
+/// Packets go in with `send` and frames come out with `receive`, as many
+/// as the decoder produces, which may be none or several per packet.
+/// `decode` combines both for the common case.
+pub struct Decoder {
+    ptr: *mut AVCodecContext,
+    codec: Codec,
+    // Received to make room for a packet, but not yet returned
+    pending: VecDeque<Frame>,
+}
+
+unsafe impl Send for Decoder {}
+
+impl Decoder {
+    /// An unopened decoder, to be configured before calling `open`.
+    pub fn new(codec: Codec) -> Result<Self, AvError> {
+        if !codec.is_decoder() {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let ptr = unsafe { avcodec_alloc_context3(codec.as_ptr()) };
+        if ptr.is_null() {
+            return Err(AvError(AVERROR(ENOMEM)));
+        }
+
+        Ok(Decoder {
+            ptr,
+            codec,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// An unopened decoder for a stream, e.g. `AVStream.codecpar`, with the
+    /// default decoder for its codec.
+    pub fn from_parameters(params: &AVCodecParameters) -> Result<Self, AvError> {
+        let codec =
+            Codec::find_decoder(params.codec_id).ok_or(AvError(AVERROR_DECODER_NOT_FOUND))?;
+        let mut decoder = Decoder::new(codec)?;
+        decoder.set_parameters(params)?;
+
+        Ok(decoder)
+    }
+
+    pub fn as_ptr(&self) -> *const AVCodecContext {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVCodecContext {
+        self.ptr
+    }
+
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    /// Copies the stream parameters, such as dimensions, formats and
+    /// extradata, into the context.
+    pub fn set_parameters(&mut self, params: &AVCodecParameters) -> Result<(), AvError> {
+        unsafe { AvError::check(avcodec_parameters_to_context(self.ptr, params)).map(|_| ()) }
+    }
+
+    /// The time base of the packet timestamps, which some decoders need for
+    /// their output timestamps and subtitle durations.
+    pub fn set_packet_time_base(&mut self, time_base: AVRational) {
+        unsafe { (*self.ptr).pkt_timebase = time_base }
+    }
+
+    pub fn open(&mut self) -> Result<(), AvError> {
+        self.open_with(&mut Dictionary::new())
+    }
+
+    /// Opens the decoder with codec and private options, e.g. `threads`.
+    /// Options that were not recognized are left in `options`.
+    pub fn open_with(&mut self, options: &mut Dictionary) -> Result<(), AvError> {
+        unsafe {
+            AvError::check(avcodec_open2(self.ptr, self.codec.as_ptr(), options.as_mut_ptr()))
+                .map(|_| ())
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        unsafe { avcodec_is_open(self.ptr) != 0 }
+    }
+
+    pub fn width(&self) -> u32 {
+        unsafe { (*self.ptr).width as u32 }
+    }
+
+    pub fn height(&self) -> u32 {
+        unsafe { (*self.ptr).height as u32 }
+    }
+
+    pub fn pixel_format(&self) -> AVPixelFormat {
+        unsafe { (*self.ptr).pix_fmt }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        unsafe { (*self.ptr).sample_rate as u32 }
+    }
+
+    pub fn sample_format(&self) -> SampleFormat {
+        SampleFormat(unsafe { (*self.ptr).sample_fmt })
+    }
+
+    pub fn channel_layout(&self) -> ChannelLayout {
+        ChannelLayout::from_bits_truncate(unsafe { (*self.ptr).channel_layout })
+    }
+
+    pub fn channels(&self) -> usize {
+        unsafe { (*self.ptr).channels as usize }
+    }
+
+    /// Feeds a packet to the decoder.
+    ///
+    /// Fails with `EAGAIN` while frames are waiting to be received, and with
+    /// `AVERROR_EOF` once draining has started. An empty packet starts
+    /// draining, like `send_eof`.
+    pub fn send(&mut self, packet: &Packet) -> Result<(), AvError> {
+        unsafe { AvError::check(avcodec_send_packet(self.ptr, packet.as_ptr())).map(|_| ()) }
+    }
+
+    /// Signals the end of the stream, so the remaining frames are output.
+    pub fn send_eof(&mut self) -> Result<(), AvError> {
+        unsafe { AvError::check(avcodec_send_packet(self.ptr, ptr::null())).map(|_| ()) }
+    }
+
+    /// The next decoded frame, or `None` if the decoder needs more input or,
+    /// after `send_eof`, has been fully drained.
+    pub fn receive(&mut self) -> Result<Option<Frame>, AvError> {
+        match self.pending.pop_front() {
+            Some(frame) => Ok(Some(frame)),
+            None => self.receive_frame(),
+        }
+    }
+
+    fn receive_frame(&mut self) -> Result<Option<Frame>, AvError> {
+        let mut frame = Frame::new();
+
+        match unsafe { avcodec_receive_frame(self.ptr, frame.as_mut_ptr()) } {
+            ret if ret == AVERROR(EAGAIN) || ret == AVERROR_EOF => Ok(None),
+            ret => AvError::check(ret).map(|_| Some(frame)),
+        }
+    }
+
+    /// Sends a packet and returns the frames it produced.
+    ///
+    /// Frames left over from a previous iterator that was not run to the end
+    /// are received first if the decoder has no room for the packet, and
+    /// come out of this iterator ahead of the new ones.
+    pub fn decode(&mut self, packet: &Packet) -> Result<DecodedFrames<'_>, AvError> {
+        self.send_queued(Some(packet))?;
+
+        Ok(DecodedFrames { decoder: self })
+    }
+
+    /// Signals the end of the stream and returns the remaining frames.
+    /// The decoder accepts packets again after a `flush`.
+    pub fn drain(&mut self) -> Result<DecodedFrames<'_>, AvError> {
+        self.send_queued(None)?;
+
+        Ok(DecodedFrames { decoder: self })
+    }
+
+    /// Sends `packet`, or the end of the stream, queueing the frames waiting
+    /// to be received while the decoder refuses input with `EAGAIN`.
+    fn send_queued(&mut self, packet: Option<&Packet>) -> Result<(), AvError> {
+        loop {
+            let result = match packet {
+                Some(packet) => self.send(packet),
+                None => self.send_eof(),
+            };
+
+            match result {
+                Err(AvError(err)) if err == AVERROR(EAGAIN) => {
+                    let queued = self.pending.len();
+                    while let Some(frame) = self.receive_frame()? {
+                        self.pending.push_back(frame);
+                    }
+
+                    // Nothing to receive either, so retrying would not help
+                    if self.pending.len() == queued {
+                        return Err(AvError(err));
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Discards buffered packets and frames, e.g. after seeking, and leaves
+    /// draining mode.
+    pub fn flush(&mut self) {
+        self.pending.clear();
+        unsafe { avcodec_flush_buffers(self.ptr) }
+    }
+}
+
+unsafe impl Options for Decoder {
+    fn as_options_ptr(&self) -> *const c_void {
+        self.ptr as *const c_void
+    }
+}
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        unsafe { avcodec_free_context(&mut self.ptr) }
+    }
+}
+
+/// Frames output by a `Decoder` for the input sent so far.
+pub struct DecodedFrames<'a> {
+    decoder: &'a mut Decoder,
+}
+
+impl<'a> Iterator for DecodedFrames<'a> {
+    type Item = Result<Frame, AvError>;
+
+    fn next(&mut self) -> Option<Result<Frame, AvError>> {
+        match self.decoder.receive() {
+            Ok(frame) => frame.map(Ok),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AVSampleFormat::AV_SAMPLE_FMT_S16;
+    use {AVCodecID, AudioFrame};
+
+    #[test]
+    fn decodes_packets_into_frames() {
+        let codec = Codec::find_decoder(AVCodecID::AV_CODEC_ID_PCM_S16LE).unwrap();
+        let mut decoder = Decoder::new(codec).unwrap();
+        decoder.set_int("ar", 8000).unwrap();
+        decoder.set_int("ac", 2).unwrap();
+        decoder.open().unwrap();
+
+        // Eight interleaved stereo samples
+        let samples = (0..16).map(|index| index * 1000 - 8000).collect::<Vec<i16>>();
+        let data = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes().to_vec())
+            .collect::<Vec<u8>>();
+        let mut packet = Packet::copy(&data).unwrap();
+        packet.set_pts(Some(42));
+
+        let frames = decoder.decode(&packet).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(frames.len(), 1);
+
+        let frame = AudioFrame::from(frames.into_iter().next().unwrap());
+        assert_eq!(frame.format(), Some(SampleFormat(AV_SAMPLE_FMT_S16)));
+        assert_eq!(frame.channels(), 2);
+        assert_eq!(frame.samples(), 8);
+        assert_eq!(frame.sample_rate(), 8000);
+        assert_eq!(frame.pts(), Some(42));
+        assert_eq!(frame.plane::<i16>(0), Some(&samples[..]));
+
+        assert_eq!(decoder.drain().unwrap().count(), 0);
+        assert_eq!(decoder.send(&packet), Err(AvError(AVERROR_EOF)));
+    }
+
+    #[test]
+    fn queues_frames_left_unreceived() {
+        let codec = Codec::find_decoder(AVCodecID::AV_CODEC_ID_PCM_S16LE).unwrap();
+        let mut decoder = Decoder::new(codec).unwrap();
+        decoder.set_int("ar", 8000).unwrap();
+        decoder.set_int("ac", 1).unwrap();
+        decoder.open().unwrap();
+
+        // The iterators are dropped without receiving anything, which
+        // leaves the decoder full by the last packet
+        for pts in 0..3 {
+            let mut packet = Packet::copy(&[0; 16]).unwrap();
+            packet.set_pts(Some(pts));
+            decoder.decode(&packet).unwrap();
+        }
+
+        let mut packet = Packet::copy(&[0; 16]).unwrap();
+        packet.set_pts(Some(3));
+        let frames = decoder.decode(&packet).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        let pts = frames.iter().map(|frame| frame.pts()).collect::<Vec<_>>();
+        assert_eq!(pts, [Some(0), Some(1), Some(2), Some(3)]);
+
+        assert_eq!(decoder.drain().unwrap().count(), 0);
+    }
+}
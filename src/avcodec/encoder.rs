@@ -0,0 +1,768 @@
+use std::collections::VecDeque;
+use std::{ptr, slice};
+
+use libc::{c_int, c_void, EAGAIN, EINVAL, ENOMEM};
+use {av_rescale_q, av_samples_set_silence, avcodec_alloc_context3, avcodec_free_context,
+     avcodec_open2, avcodec_parameters_from_context, avcodec_receive_packet, avcodec_send_frame,
+     AVCodecContext, AVCodecParameters, AVPixelFormat, AVRational, AudioFifo, AudioFrame,
+     AvError, ChannelLayout, Codec, CodecCapabilities, Dictionary, Frame, Options, Packet,
+     SampleFormat, AVERROR, AVERROR_EOF, AV_CODEC_FLAG_GLOBAL_HEADER};
+use AVMediaType::AVMEDIA_TYPE_AUDIO;
+
+/// Settings for an `Encoder`, checked against what the codec supports when
+/// it is opened.
+pub struct EncoderBuilder {
+    codec: Codec,
+    time_base: Option<AVRational>,
+    frame_rate: Option<AVRational>,
+    size: Option<(u32, u32)>,
+    pixel_format: Option<AVPixelFormat>,
+    sample_rate: Option<u32>,
+    sample_format: Option<SampleFormat>,
+    channel_layout: Option<ChannelLayout>,
+    bit_rate: Option<i64>,
+    gop_size: Option<u32>,
+    max_b_frames: Option<u32>,
+    global_header: bool,
+}
+
+impl EncoderBuilder {
+    pub fn new(codec: Codec) -> Self {
+        EncoderBuilder {
+            codec,
+            time_base: None,
+            frame_rate: None,
+            size: None,
+            pixel_format: None,
+            sample_rate: None,
+            sample_format: None,
+            channel_layout: None,
+            bit_rate: None,
+            gop_size: None,
+            max_b_frames: None,
+            global_header: false,
+        }
+    }
+
+    /// The time base of the frame and packet timestamps. Defaults to
+    /// `1/sample_rate` for audio and to the inverse of the frame rate for
+    /// video.
+    pub fn time_base(mut self, time_base: AVRational) -> Self {
+        self.time_base = Some(time_base);
+        self
+    }
+
+    pub fn frame_rate(mut self, frame_rate: AVRational) -> Self {
+        self.frame_rate = Some(frame_rate);
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    pub fn pixel_format(mut self, format: AVPixelFormat) -> Self {
+        self.pixel_format = Some(format);
+        self
+    }
+
+    pub fn sample_rate(mut self, rate: u32) -> Self {
+        self.sample_rate = Some(rate);
+        self
+    }
+
+    pub fn sample_format(mut self, format: SampleFormat) -> Self {
+        self.sample_format = Some(format);
+        self
+    }
+
+    /// Also sets the number of channels.
+    pub fn channel_layout(mut self, layout: ChannelLayout) -> Self {
+        self.channel_layout = Some(layout);
+        self
+    }
+
+    /// Average bit rate in bits per second.
+    pub fn bit_rate(mut self, bit_rate: i64) -> Self {
+        self.bit_rate = Some(bit_rate);
+        self
+    }
+
+    /// Maximum number of frames between keyframes.
+    pub fn gop_size(mut self, size: u32) -> Self {
+        self.gop_size = Some(size);
+        self
+    }
+
+    pub fn max_b_frames(mut self, count: u32) -> Self {
+        self.max_b_frames = Some(count);
+        self
+    }
+
+    /// Puts the global headers in `extradata` rather than in every
+    /// keyframe, as containers such as MP4 and Matroska require.
+    pub fn global_header(mut self, enable: bool) -> Self {
+        self.global_header = enable;
+        self
+    }
+
+    pub fn open(self) -> Result<Encoder, AvError> {
+        self.open_with(&mut Dictionary::new())
+    }
+
+    /// Opens the encoder with codec and private options, e.g. `crf`.
+    /// Options that were not recognized are left in `options`.
+    ///
+    /// Fails with `EINVAL` if the codec is not an encoder, a format, rate
+    /// or layout is not among those it supports, or no time base is known.
+    pub fn open_with(self, options: &mut Dictionary) -> Result<Encoder, AvError> {
+        self.validate()?;
+
+        let time_base = self.time_base
+            .or_else(|| self.sample_rate.map(|rate| rational(1, rate as c_int)))
+            .or_else(|| self.frame_rate.map(|rate| rational(rate.den, rate.num)))
+            .ok_or(AvError(AVERROR(EINVAL)))?;
+        if time_base.num <= 0 || time_base.den <= 0 {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let ptr = unsafe { avcodec_alloc_context3(self.codec.as_ptr()) };
+        if ptr.is_null() {
+            return Err(AvError(AVERROR(ENOMEM)));
+        }
+        let mut encoder = Encoder {
+            ptr,
+            codec: self.codec,
+            fifo: None,
+            timestamps: VecDeque::new(),
+            written: 0,
+            read: 0,
+            draining: false,
+            eof_sent: false,
+        };
+
+        unsafe {
+            let ctx = &mut *ptr;
+
+            ctx.time_base = time_base;
+            if let Some(rate) = self.frame_rate {
+                ctx.framerate = rate;
+            }
+            if let Some((width, height)) = self.size {
+                ctx.width = width as c_int;
+                ctx.height = height as c_int;
+            }
+            if let Some(format) = self.pixel_format {
+                ctx.pix_fmt = format;
+            }
+            if let Some(rate) = self.sample_rate {
+                ctx.sample_rate = rate as c_int;
+            }
+            if let Some(format) = self.sample_format {
+                ctx.sample_fmt = format.0;
+            }
+            if let Some(layout) = self.channel_layout {
+                ctx.channel_layout = layout.bits();
+                ctx.channels = layout.nb_channels() as c_int;
+            }
+            if let Some(bit_rate) = self.bit_rate {
+                ctx.bit_rate = bit_rate;
+            }
+            if let Some(size) = self.gop_size {
+                ctx.gop_size = size as c_int;
+            }
+            if let Some(count) = self.max_b_frames {
+                ctx.max_b_frames = count as c_int;
+            }
+            if self.global_header {
+                ctx.flags |= AV_CODEC_FLAG_GLOBAL_HEADER as c_int;
+            }
+
+            AvError::check(avcodec_open2(ptr, self.codec.as_ptr(), options.as_mut_ptr()))?;
+        }
+
+        // The frame size is only known once the codec is open
+        if self.codec.media_type() == AVMEDIA_TYPE_AUDIO
+            && !self.codec
+                .capabilities()
+                .contains(CodecCapabilities::VARIABLE_FRAME_SIZE)
+        {
+            if let Some(frame_size) = encoder.frame_size() {
+                let fifo = AudioFifo::new(encoder.sample_format(), encoder.channels(), frame_size)?;
+                encoder.fifo = Some(fifo);
+            }
+        }
+
+        Ok(encoder)
+    }
+
+    fn validate(&self) -> Result<(), AvError> {
+        let codec = &self.codec;
+        // Compared as pairs, as `AVRational` has no `PartialEq`
+        let frame_rates = codec
+            .frame_rates()
+            .map(|rates| rates.iter().map(|rate| (rate.num, rate.den)).collect::<Vec<_>>());
+        let frame_rate = self.frame_rate.map(|rate| (rate.num, rate.den));
+        let sample_rate = self.sample_rate.map(|rate| rate as c_int);
+        let sample_formats = codec.sample_formats();
+        let channel_layouts = codec.channel_layouts();
+
+        let supported = codec.is_encoder()
+            && supports(frame_rates.as_ref().map(|r| &r[..]), frame_rate.as_ref())
+            && supports(codec.pixel_formats(), self.pixel_format.as_ref())
+            && supports(codec.sample_rates(), sample_rate.as_ref())
+            && supports(sample_formats.as_ref().map(|f| &f[..]), self.sample_format.as_ref())
+            && supports(channel_layouts.as_ref().map(|l| &l[..]), self.channel_layout.as_ref());
+
+        if supported {
+            Ok(())
+        } else {
+            Err(AvError(AVERROR(EINVAL)))
+        }
+    }
+}
+
+/// Whether `value`, if set, is in the list of supported values, if known.
+fn supports<T: PartialEq>(supported: Option<&[T]>, value: Option<&T>) -> bool {
+    match (supported, value) {
+        (Some(supported), Some(value)) => supported.contains(value),
+        _ => true,
+    }
+}
+
+fn rational(num: c_int, den: c_int) -> AVRational {
+    AVRational { num, den }
+}
+
+/// An open encoder, owning its `AVCodecContext`.
+///
+/// Frames go in with `encode` and packets come out of the returned
+/// iterator, timestamped in the encoder's time base. Audio for codecs that
+/// take a fixed `frame_size` is regrouped into frames of that size first, so
+/// frames of any length can be passed in.
+pub struct Encoder {
+    ptr: *mut AVCodecContext,
+    codec: Codec,
+    // Audio waiting to make up a whole frame
+    fifo: Option<AudioFifo>,
+    // Timestamps of the frames written to the fifo, by the position of their
+    // first sample among all samples written, and the positions reached by
+    // writing and reading
+    timestamps: VecDeque<(i64, Option<i64>)>,
+    written: i64,
+    read: i64,
+    draining: bool,
+    eof_sent: bool,
+}
+
+unsafe impl Send for Encoder {}
+
+impl Encoder {
+    pub fn as_ptr(&self) -> *const AVCodecContext {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVCodecContext {
+        self.ptr
+    }
+
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    pub fn time_base(&self) -> AVRational {
+        unsafe { (*self.ptr).time_base }
+    }
+
+    pub fn width(&self) -> u32 {
+        unsafe { (*self.ptr).width as u32 }
+    }
+
+    pub fn height(&self) -> u32 {
+        unsafe { (*self.ptr).height as u32 }
+    }
+
+    pub fn pixel_format(&self) -> AVPixelFormat {
+        unsafe { (*self.ptr).pix_fmt }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        unsafe { (*self.ptr).sample_rate as u32 }
+    }
+
+    pub fn sample_format(&self) -> SampleFormat {
+        SampleFormat(unsafe { (*self.ptr).sample_fmt })
+    }
+
+    pub fn channel_layout(&self) -> ChannelLayout {
+        ChannelLayout::from_bits_truncate(unsafe { (*self.ptr).channel_layout })
+    }
+
+    pub fn channels(&self) -> usize {
+        unsafe { (*self.ptr).channels as usize }
+    }
+
+    /// Samples per channel the codec takes in each audio frame, `None` for
+    /// video and for audio codecs accepting any size.
+    pub fn frame_size(&self) -> Option<usize> {
+        match unsafe { (*self.ptr).frame_size } {
+            size if size <= 0 => None,
+            size => Some(size as usize),
+        }
+    }
+
+    /// Global headers of the stream, empty unless set up with
+    /// `global_header` or required by the codec.
+    pub fn extradata(&self) -> &[u8] {
+        unsafe {
+            let ctx = &*self.ptr;
+
+            if ctx.extradata.is_null() || ctx.extradata_size <= 0 {
+                &[]
+            } else {
+                slice::from_raw_parts(ctx.extradata, ctx.extradata_size as usize)
+            }
+        }
+    }
+
+    /// Copies the stream parameters, including extradata, e.g. into the
+    /// `AVStream.codecpar` of an output stream.
+    pub fn parameters_into(&self, params: &mut AVCodecParameters) -> Result<(), AvError> {
+        unsafe { AvError::check(avcodec_parameters_from_context(params, self.ptr)).map(|_| ()) }
+    }
+
+    /// Feeds a frame to the encoder as is, bypassing the regrouping of
+    /// audio that `encode` does.
+    ///
+    /// Fails with `EAGAIN` while packets are waiting to be received, and
+    /// with `AVERROR_EOF` once draining has started.
+    pub fn send(&mut self, frame: &Frame) -> Result<(), AvError> {
+        unsafe { AvError::check(avcodec_send_frame(self.ptr, frame.as_ptr())).map(|_| ()) }
+    }
+
+    /// Signals the end of the stream, so the remaining packets are output.
+    /// Audio still waiting in `encode` is not sent, use `drain` for that.
+    pub fn send_eof(&mut self) -> Result<(), AvError> {
+        unsafe { AvError::check(avcodec_send_frame(self.ptr, ptr::null())).map(|_| ()) }
+    }
+
+    /// The next encoded packet, or `None` if the encoder needs more input
+    /// or, after `send_eof`, has been fully drained.
+    pub fn receive(&mut self) -> Result<Option<Packet>, AvError> {
+        let mut packet = Packet::new();
+
+        match unsafe { avcodec_receive_packet(self.ptr, packet.as_mut_ptr()) } {
+            ret if ret == AVERROR(EAGAIN) || ret == AVERROR_EOF => Ok(None),
+            ret => AvError::check(ret).map(|_| Some(packet)),
+        }
+    }
+
+    /// Encodes a frame and returns the packets it produced.
+    ///
+    /// Audio frames must match the sample format and channel count of the
+    /// encoder, or this fails with `EINVAL`. A regrouped frame takes the
+    /// timestamp of the frame its first sample came from, counting on by
+    /// the samples before it, so a gap between frames shows up at the first
+    /// regrouped frame starting after it.
+    ///
+    /// The packets must all be received before the next frame is sent, so
+    /// the iterator should be run to the end.
+    pub fn encode(&mut self, frame: &Frame) -> Result<EncodedPackets<'_>, AvError> {
+        if self.draining {
+            return Err(AvError(AVERROR_EOF));
+        }
+
+        let (format, channels) = (self.sample_format(), self.channels());
+
+        match self.fifo {
+            Some(ref mut fifo) => {
+                let frame = AudioFrame::from(frame.clone());
+                if frame.format() != Some(format) || frame.channels() != channels {
+                    return Err(AvError(AVERROR(EINVAL)));
+                }
+
+                fifo.write(&frame)?;
+                self.timestamps.push_back((self.written, frame.pts()));
+                self.written += frame.samples() as i64;
+            }
+            None => self.send(frame)?,
+        }
+
+        Ok(EncodedPackets { encoder: self })
+    }
+
+    /// Encodes the remaining audio and signals the end of the stream, then
+    /// returns the remaining packets.
+    ///
+    /// A last partial audio frame is padded with silence unless the codec
+    /// accepts a smaller last frame.
+    pub fn drain(&mut self) -> Result<EncodedPackets<'_>, AvError> {
+        self.draining = true;
+
+        Ok(EncodedPackets { encoder: self })
+    }
+
+    // Sends the next regrouped frame, or the end of the stream once drained,
+    // returning whether anything was sent
+    fn feed(&mut self) -> Result<bool, AvError> {
+        if let Some(frame) = self.next_frame()? {
+            self.send(&frame)?;
+            return Ok(true);
+        }
+
+        if self.draining && !self.eof_sent {
+            self.send_eof()?;
+            self.eof_sent = true;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn next_frame(&mut self) -> Result<Option<AudioFrame>, AvError> {
+        let frame_size = match self.frame_size() {
+            Some(size) => size,
+            None => return Ok(None),
+        };
+        let small_last_frame = self.codec
+            .capabilities()
+            .contains(CodecCapabilities::SMALL_LAST_FRAME);
+        let (format, layout, channels) =
+            (self.sample_format(), self.channel_layout(), self.channels());
+        let (sample_rate, time_base) = (self.sample_rate(), self.time_base());
+
+        let fifo = match self.fifo {
+            Some(ref mut fifo) => fifo,
+            None => return Ok(None),
+        };
+        let buffered = fifo.len();
+
+        if buffered == 0 || (buffered < frame_size && !self.draining) {
+            return Ok(None);
+        }
+        if buffered < frame_size && !small_last_frame {
+            let mut silence = AudioFrame::new(format, layout, frame_size - buffered)?;
+            unsafe {
+                AvError::check(av_samples_set_silence(
+                    (*silence.as_mut_ptr()).extended_data,
+                    0,
+                    (frame_size - buffered) as c_int,
+                    channels as c_int,
+                    format.0,
+                ))?;
+            }
+            fifo.write(&silence)?;
+            self.written += silence.samples() as i64;
+        }
+
+        let mut frame = match fifo.read_frame(frame_size)? {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        // The last frame written starting at or before the first sample read
+        let start = self.read;
+        while self.timestamps.len() > 1 && self.timestamps[1].0 <= start {
+            self.timestamps.pop_front();
+        }
+        let sample_time_base = rational(1, sample_rate as c_int);
+        let pts = match self.timestamps.front() {
+            Some(&(position, Some(pts))) => {
+                Some(pts + unsafe { av_rescale_q(start - position, sample_time_base, time_base) })
+            }
+            _ => None,
+        };
+
+        frame.set_pts(pts);
+        frame.set_sample_rate(sample_rate);
+        self.read += frame.samples() as i64;
+
+        Ok(Some(frame))
+    }
+}
+
+unsafe impl Options for Encoder {
+    fn as_options_ptr(&self) -> *const c_void {
+        self.ptr as *const c_void
+    }
+}
+
+impl Drop for Encoder {
+    fn drop(&mut self) {
+        unsafe { avcodec_free_context(&mut self.ptr) }
+    }
+}
+
+/// Packets output by an `Encoder` for the input sent so far.
+pub struct EncodedPackets<'a> {
+    encoder: &'a mut Encoder,
+}
+
+impl<'a> Iterator for EncodedPackets<'a> {
+    type Item = Result<Packet, AvError>;
+
+    fn next(&mut self) -> Option<Result<Packet, AvError>> {
+        loop {
+            match self.encoder.receive() {
+                Ok(Some(packet)) => return Some(Ok(packet)),
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
+
+            // Nothing ready, so the encoder needs more of the buffered input
+            match self.encoder.feed() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AVPixelFormat::{AV_PIX_FMT_GRAY8, AV_PIX_FMT_YUV420P};
+    use AVSampleFormat::{AV_SAMPLE_FMT_S16, AV_SAMPLE_FMT_S32};
+    use {avcodec_parameters_alloc, avcodec_parameters_free, Decoder, VideoFrame};
+
+    // MP2 takes frames of 1152 samples and has no small last frame
+    fn mp2() -> Encoder {
+        EncoderBuilder::new(Codec::find_encoder_by_name("mp2").unwrap())
+            .sample_rate(48000)
+            .sample_format(SampleFormat(AV_SAMPLE_FMT_S16))
+            .channel_layout(ChannelLayout::STEREO)
+            .bit_rate(128_000)
+            .open()
+            .unwrap()
+    }
+
+    fn audio(samples: usize, pts: i64) -> AudioFrame {
+        let format = SampleFormat(AV_SAMPLE_FMT_S16);
+        let mut frame = AudioFrame::new(format, ChannelLayout::STEREO, samples).unwrap();
+        for (index, sample) in frame.plane_mut::<i16>(0).unwrap().iter_mut().enumerate() {
+            *sample = ((index % 100) as i16 - 50) * 200;
+        }
+        frame.set_pts(Some(pts));
+        frame.set_sample_rate(48000);
+
+        frame
+    }
+
+    fn encode(encoder: &mut Encoder, frames: &[AudioFrame]) -> Vec<Packet> {
+        let mut packets = Vec::new();
+        for frame in frames {
+            packets.extend(encoder.encode(frame).unwrap().map(Result::unwrap));
+        }
+        packets.extend(encoder.drain().unwrap().map(Result::unwrap));
+
+        packets
+    }
+
+    fn decode(decoder: &mut Decoder, packets: &[Packet]) -> Vec<Frame> {
+        let mut frames = Vec::new();
+        for packet in packets {
+            frames.extend(decoder.decode(packet).unwrap().map(Result::unwrap));
+        }
+        frames.extend(decoder.drain().unwrap().map(Result::unwrap));
+
+        frames
+    }
+
+    struct Parameters(*mut AVCodecParameters);
+
+    impl Parameters {
+        fn of(encoder: &Encoder) -> Parameters {
+            let params = Parameters(unsafe { avcodec_parameters_alloc() });
+            assert!(!params.0.is_null());
+            encoder.parameters_into(unsafe { &mut *params.0 }).unwrap();
+
+            params
+        }
+
+        fn extradata(&self) -> &[u8] {
+            unsafe {
+                let params = &*self.0;
+
+                if params.extradata.is_null() || params.extradata_size <= 0 {
+                    &[]
+                } else {
+                    slice::from_raw_parts(params.extradata, params.extradata_size as usize)
+                }
+            }
+        }
+    }
+
+    impl Drop for Parameters {
+        fn drop(&mut self) {
+            unsafe { avcodec_parameters_free(&mut self.0) }
+        }
+    }
+
+    // The visible bytes of each line of the first plane
+    fn pixels(frame: &VideoFrame) -> Vec<u8> {
+        let (width, stride) = (frame.width() as usize, frame.stride(0) as usize);
+        let plane = frame.plane(0).unwrap();
+
+        (0..frame.height() as usize)
+            .flat_map(|line| plane[line * stride..][..width].to_vec())
+            .collect()
+    }
+
+    // The codec shifts packet timestamps by its delay, so only the steps
+    // between them are compared
+    fn pts_steps(packets: &[Packet]) -> Vec<i64> {
+        packets
+            .windows(2)
+            .map(|pair| pair[1].pts().unwrap() - pair[0].pts().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn regroups_and_pads_audio() {
+        let mut encoder = mp2();
+        assert_eq!(encoder.frame_size(), Some(1152));
+
+        // 5000 samples make four whole frames and one padded with silence
+        let frames = (0..5).map(|index| audio(1000, index * 1000)).collect::<Vec<_>>();
+        let packets = encode(&mut encoder, &frames);
+        assert_eq!(packets.len(), 5);
+        assert_eq!(pts_steps(&packets), [1152; 4]);
+        assert_eq!(encoder.encode(&frames[0]).err(), Some(AvError(AVERROR_EOF)));
+
+        let mut decoder = Decoder::new(Codec::find_decoder_by_name("mp2").unwrap()).unwrap();
+        decoder.open().unwrap();
+
+        let frames = decode(&mut decoder, &packets);
+        let frames = frames.into_iter().map(AudioFrame::from).collect::<Vec<_>>();
+        assert!(frames.iter().all(|frame| frame.channels() == 2 && frame.sample_rate() == 48000));
+        assert_eq!(frames.iter().map(|frame| frame.samples()).sum::<usize>(), 5 * 1152);
+    }
+
+    #[test]
+    fn carries_timestamps_over_gaps() {
+        let mut encoder = mp2();
+
+        // Gaps at the boundary of the first regrouped frame, then inside the
+        // second and third ones
+        let frames = [
+            audio(1152, 0),
+            audio(1000, 10_000),
+            audio(1000, 20_000),
+            audio(1000, 30_000),
+        ];
+        let packets = encode(&mut encoder, &frames);
+
+        // Regrouped frames start at samples 0, 1152, 2304 and 3456, which
+        // come from the frames starting at samples 0, 1152, 2152 and 3152
+        assert_eq!(packets.len(), 4);
+        assert_eq!(pts_steps(&packets), [10_000, 20_152 - 10_000, 30_304 - 20_152]);
+    }
+
+    #[test]
+    fn rejects_mismatched_audio() {
+        let mut encoder = mp2();
+        let mono =
+            AudioFrame::new(SampleFormat(AV_SAMPLE_FMT_S16), ChannelLayout::MONO, 1152).unwrap();
+        let s32 =
+            AudioFrame::new(SampleFormat(AV_SAMPLE_FMT_S32), ChannelLayout::STEREO, 1152).unwrap();
+
+        assert_eq!(encoder.encode(&mono).err(), Some(AvError(AVERROR(EINVAL))));
+        assert_eq!(encoder.encode(&s32).err(), Some(AvError(AVERROR(EINVAL))));
+        assert_eq!(encode(&mut encoder, &[audio(1152, 0)]).len(), 1);
+    }
+
+    #[test]
+    fn round_trips_pcm() {
+        let mut encoder = EncoderBuilder::new(Codec::find_encoder_by_name("pcm_s16le").unwrap())
+            .sample_rate(48000)
+            .sample_format(SampleFormat(AV_SAMPLE_FMT_S16))
+            .channel_layout(ChannelLayout::STEREO)
+            .open()
+            .unwrap();
+        // Any frame size is accepted, so frames are sent as they come
+        assert_eq!(encoder.frame_size(), None);
+
+        let frames = [audio(1000, 0), audio(300, 1000), audio(1152, 1300)];
+        let packets = encode(&mut encoder, &frames);
+        assert_eq!(packets.len(), 3);
+
+        let params = Parameters::of(&encoder);
+        let mut decoder = Decoder::from_parameters(unsafe { &*params.0 }).unwrap();
+        decoder.open().unwrap();
+        let decoded = decode(&mut decoder, &packets);
+        let decoded = decoded.into_iter().map(AudioFrame::from).collect::<Vec<_>>();
+        let pts = decoded.iter().map(|frame| frame.pts()).collect::<Vec<_>>();
+        assert_eq!(pts, [Some(0), Some(1000), Some(1300)]);
+
+        let samples = |frames: &[AudioFrame]| -> Vec<i16> {
+            frames.iter().flat_map(|frame| frame.plane::<i16>(0).unwrap().to_vec()).collect()
+        };
+        assert_eq!(samples(&decoded), samples(&frames));
+    }
+
+    #[test]
+    fn round_trips_raw_video() {
+        let mut encoder = EncoderBuilder::new(Codec::find_encoder_by_name("rawvideo").unwrap())
+            .size(16, 8)
+            .pixel_format(AV_PIX_FMT_GRAY8)
+            .frame_rate(rational(25, 1))
+            .global_header(true)
+            .open()
+            .unwrap();
+        let time_base = encoder.time_base();
+        assert_eq!((time_base.num, time_base.den), (1, 25));
+
+        let frames = (0..3)
+            .map(|index| {
+                let mut frame = VideoFrame::new(AV_PIX_FMT_GRAY8, 16, 8).unwrap();
+                for (offset, pixel) in frame.plane_mut(0).unwrap().iter_mut().enumerate() {
+                    *pixel = (offset as i64 * 7 + index) as u8;
+                }
+                frame.set_pts(Some(index));
+
+                frame
+            })
+            .collect::<Vec<_>>();
+
+        let mut packets = Vec::new();
+        for frame in &frames {
+            packets.extend(encoder.encode(frame).unwrap().map(Result::unwrap));
+        }
+        packets.extend(encoder.drain().unwrap().map(Result::unwrap));
+        assert_eq!(packets.len(), 3);
+        assert!(packets.iter().all(|packet| packet.data().len() == 16 * 8));
+
+        // Raw video has no global headers to move into the extradata
+        let params = Parameters::of(&encoder);
+        assert_eq!(params.extradata(), encoder.extradata());
+
+        let mut decoder = Decoder::from_parameters(unsafe { &*params.0 }).unwrap();
+        decoder.open().unwrap();
+        let decoded = decode(&mut decoder, &packets);
+        assert_eq!(decoded.len(), 3);
+
+        for (frame, decoded) in frames.iter().zip(decoded.into_iter().map(VideoFrame::from)) {
+            assert_eq!(decoded.format(), Some(AV_PIX_FMT_GRAY8));
+            assert_eq!((decoded.width(), decoded.height()), (16, 8));
+            assert_eq!(decoded.pts(), frame.pts());
+            assert_eq!(pixels(&decoded), pixels(frame));
+        }
+    }
+
+    #[test]
+    fn moves_global_headers_into_parameters() {
+        let encoder = EncoderBuilder::new(Codec::find_encoder_by_name("mpeg4").unwrap())
+            .size(16, 16)
+            .pixel_format(AV_PIX_FMT_YUV420P)
+            .frame_rate(rational(25, 1))
+            .global_header(true)
+            .open()
+            .unwrap();
+
+        let params = Parameters::of(&encoder);
+        assert!(!encoder.extradata().is_empty());
+        assert_eq!(params.extradata(), encoder.extradata());
+    }
+}
@@ -0,0 +1,11 @@
+mod codec;
+pub use self::codec::*;
+
+mod packet;
+pub use self::packet::*;
+
+mod decoder;
+pub use self::decoder::*;
+
+mod encoder;
+pub use self::encoder::*;
@@ -0,0 +1,206 @@
+use std::slice;
+
+use libc::{c_int, EINVAL};
+use {av_new_packet, av_packet_alloc, av_packet_clone, av_packet_free, av_packet_from_data,
+     av_packet_ref, av_packet_rescale_ts, av_packet_unref, timestamp, AVPacket, AVRational,
+     AvError, AvVec, AVERROR, AV_NOPTS_VALUE};
+use {AV_PKT_FLAG_CORRUPT, AV_PKT_FLAG_DISCARD, AV_PKT_FLAG_DISPOSABLE, AV_PKT_FLAG_KEY,
+     AV_PKT_FLAG_TRUSTED};
+
+bitflags! {
+    pub struct PacketFlags: c_int {
+        const KEY        = AV_PKT_FLAG_KEY;
+        const CORRUPT    = AV_PKT_FLAG_CORRUPT;
+        /// Needed to decode the stream but must be dropped after decoding.
+        const DISCARD    = AV_PKT_FLAG_DISCARD;
+        const TRUSTED    = AV_PKT_FLAG_TRUSTED;
+        /// No other frames depend on this one.
+        const DISPOSABLE = AV_PKT_FLAG_DISPOSABLE;
+    }
+}
+
+/// An owned `AVPacket` of compressed data, held through a reference
+/// counted buffer when it has any.
+///
+/// Cloning adds a reference to the same data rather than copying it.
+pub struct Packet {
+    ptr: *mut AVPacket,
+}
+
+unsafe impl Send for Packet {}
+
+impl Packet {
+    /// An empty packet, as sent to flush a decoder.
+    pub fn new() -> Self {
+        let ptr = unsafe { av_packet_alloc() };
+        assert!(!ptr.is_null(), "av_packet_alloc: out of memory");
+
+        Packet { ptr }
+    }
+
+    /// A packet with a padded copy of `data`.
+    pub fn copy(data: &[u8]) -> Result<Self, AvError> {
+        if data.len() > c_int::max_value() as usize {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let packet = Packet::new();
+        unsafe {
+            AvError::check(av_new_packet(packet.ptr, data.len() as c_int))?;
+            if !data.is_empty() {
+                slice::from_raw_parts_mut((*packet.ptr).data, data.len()).copy_from_slice(data);
+            }
+        }
+
+        Ok(packet)
+    }
+
+    /// A packet taking ownership of already padded data without copying it.
+    pub fn from_vec(data: AvVec) -> Result<Self, AvError> {
+        if data.len() > c_int::max_value() as usize {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let packet = Packet::new();
+        let (ptr, len) = data.into_raw();
+
+        unsafe {
+            // On failure the data is still ours to free
+            if let Err(err) = AvError::check(av_packet_from_data(packet.ptr, ptr, len as c_int)) {
+                drop(AvVec::from_raw(ptr, len));
+                return Err(err);
+            }
+        }
+
+        Ok(packet)
+    }
+
+    /// Takes ownership of a packet allocated with `av_packet_alloc`.
+    pub unsafe fn from_raw(ptr: *mut AVPacket) -> Self {
+        Packet { ptr }
+    }
+
+    pub fn into_raw(self) -> *mut AVPacket {
+        let ptr = self.ptr;
+        ::std::mem::forget(self);
+
+        ptr
+    }
+
+    pub fn as_ptr(&self) -> *const AVPacket {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVPacket {
+        self.ptr
+    }
+
+    pub fn is_empty(&self) -> bool {
+        unsafe { (*self.ptr).size == 0 }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        unsafe {
+            if (*self.ptr).data.is_null() || (*self.ptr).size <= 0 {
+                &[]
+            } else {
+                slice::from_raw_parts((*self.ptr).data, (*self.ptr).size as usize)
+            }
+        }
+    }
+
+    /// Replaces the packet with a new reference to the data and properties
+    /// of `src`.
+    pub fn ref_from(&mut self, src: &Packet) -> Result<(), AvError> {
+        unsafe {
+            av_packet_unref(self.ptr);
+            AvError::check(av_packet_ref(self.ptr, src.ptr)).map(|_| ())
+        }
+    }
+
+    /// Drops the data and resets every field to its default.
+    pub fn unref(&mut self) {
+        unsafe { av_packet_unref(self.ptr) }
+    }
+
+    /// The presentation timestamp, in the time base of the stream.
+    pub fn pts(&self) -> Option<i64> {
+        unsafe { timestamp((*self.ptr).pts) }
+    }
+
+    pub fn set_pts(&mut self, pts: Option<i64>) {
+        unsafe { (*self.ptr).pts = pts.unwrap_or(AV_NOPTS_VALUE) }
+    }
+
+    /// The decompression timestamp, in the time base of the stream.
+    pub fn dts(&self) -> Option<i64> {
+        unsafe { timestamp((*self.ptr).dts) }
+    }
+
+    pub fn set_dts(&mut self, dts: Option<i64>) {
+        unsafe { (*self.ptr).dts = dts.unwrap_or(AV_NOPTS_VALUE) }
+    }
+
+    /// Duration in the time base of the stream, 0 if unknown.
+    pub fn duration(&self) -> i64 {
+        unsafe { (*self.ptr).duration }
+    }
+
+    pub fn set_duration(&mut self, duration: i64) {
+        unsafe { (*self.ptr).duration = duration }
+    }
+
+    /// Converts the timestamps and duration from one time base to another.
+    pub fn rescale_ts(&mut self, from: AVRational, to: AVRational) {
+        unsafe { av_packet_rescale_ts(self.ptr, from, to) }
+    }
+
+    pub fn stream_index(&self) -> usize {
+        unsafe { (*self.ptr).stream_index as usize }
+    }
+
+    pub fn set_stream_index(&mut self, index: usize) {
+        unsafe { (*self.ptr).stream_index = index as c_int }
+    }
+
+    pub fn flags(&self) -> PacketFlags {
+        PacketFlags::from_bits_truncate(unsafe { (*self.ptr).flags })
+    }
+
+    pub fn set_flags(&mut self, flags: PacketFlags) {
+        unsafe { (*self.ptr).flags = flags.bits() }
+    }
+
+    pub fn is_key(&self) -> bool {
+        self.flags().contains(PacketFlags::KEY)
+    }
+
+    /// Byte position in the input, if known.
+    pub fn position(&self) -> Option<u64> {
+        match unsafe { (*self.ptr).pos } {
+            pos if pos < 0 => None,
+            pos => Some(pos as u64),
+        }
+    }
+}
+
+impl Default for Packet {
+    fn default() -> Self {
+        Packet::new()
+    }
+}
+
+impl Clone for Packet {
+    fn clone(&self) -> Self {
+        let ptr = unsafe { av_packet_clone(self.ptr) };
+        assert!(!ptr.is_null(), "av_packet_clone: out of memory");
+
+        Packet { ptr }
+    }
+}
+
+impl Drop for Packet {
+    fn drop(&mut self) {
+        unsafe { av_packet_free(&mut self.ptr) }
+    }
+}
@@ -0,0 +1,203 @@
+use std::cmp;
+
+use libc::{c_int, c_void, EINVAL, ENOMEM};
+use {av_audio_fifo_alloc, av_audio_fifo_drain, av_audio_fifo_free, av_audio_fifo_peek,
+     av_audio_fifo_read, av_audio_fifo_realloc, av_audio_fifo_reset, av_audio_fifo_size,
+     av_audio_fifo_space, av_audio_fifo_write, AVAudioFifo, AudioFrame, AvError, ChannelLayout,
+     Frame, SampleFormat, AVERROR};
+
+/// A FIFO of audio samples, growing as needed on writes.
+///
+/// Typically used to regroup decoded or resampled audio into the fixed
+/// `frame_size` an encoder expects.
+pub struct AudioFifo {
+    ptr: *mut AVAudioFifo,
+    format: SampleFormat,
+    channels: usize,
+    layout: ChannelLayout,
+}
+
+unsafe impl Send for AudioFifo {}
+
+impl AudioFifo {
+    /// A FIFO with room for `capacity` samples per channel before it needs
+    /// to grow.
+    pub fn new(format: SampleFormat, channels: usize, capacity: usize) -> Result<Self, AvError> {
+        let ptr = unsafe { av_audio_fifo_alloc(format.0, channels as c_int, capacity as c_int) };
+
+        if ptr.is_null() {
+            Err(AvError(AVERROR(ENOMEM)))
+        } else {
+            Ok(AudioFifo {
+                ptr,
+                format,
+                channels,
+                layout: ChannelLayout::empty(),
+            })
+        }
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVAudioFifo {
+        self.ptr
+    }
+
+    pub fn format(&self) -> SampleFormat {
+        self.format
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Number of samples per channel available for reading.
+    pub fn len(&self) -> usize {
+        unsafe { av_audio_fifo_size(self.ptr) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of samples per channel that can be written without growing.
+    pub fn space(&self) -> usize {
+        unsafe { av_audio_fifo_space(self.ptr) as usize }
+    }
+
+    /// Grows or shrinks the FIFO to hold `capacity` samples per channel.
+    pub fn realloc(&mut self, capacity: usize) -> Result<(), AvError> {
+        unsafe { AvError::check(av_audio_fifo_realloc(self.ptr, capacity as c_int)).map(|_| ()) }
+    }
+
+    /// Appends every sample of a frame with the FIFO's format and channel
+    /// count.
+    pub fn write(&mut self, frame: &AudioFrame) -> Result<(), AvError> {
+        self.check(frame)?;
+        if !frame.channel_layout().is_empty() {
+            self.layout = frame.channel_layout();
+        }
+
+        unsafe {
+            let data = (*frame.as_ptr()).extended_data as *mut *mut c_void;
+            AvError::check(av_audio_fifo_write(self.ptr, data, frame.samples() as c_int))
+                .map(|_| ())
+        }
+    }
+
+    /// Moves up to `samples` samples into the frame, which must have room
+    /// for them, and sets its sample count to the number read.
+    pub fn read_into(&mut self, frame: &mut AudioFrame, samples: usize) -> Result<usize, AvError> {
+        self.check(frame)?;
+        if samples > frame.samples() {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+        frame.make_writable()?;
+
+        unsafe {
+            let frame = frame.as_mut_ptr();
+            let data = (*frame).extended_data as *mut *mut c_void;
+            let read = AvError::check(av_audio_fifo_read(self.ptr, data, samples as c_int))?;
+            (*frame).nb_samples = read;
+
+            Ok(read as usize)
+        }
+    }
+
+    /// Like `read_into`, without removing the samples from the FIFO.
+    pub fn peek(&self, frame: &mut AudioFrame, samples: usize) -> Result<usize, AvError> {
+        self.check(frame)?;
+        if samples > frame.samples() {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+        frame.make_writable()?;
+
+        unsafe {
+            let frame = frame.as_mut_ptr();
+            let data = (*frame).extended_data as *mut *mut c_void;
+            let read = AvError::check(av_audio_fifo_peek(self.ptr, data, samples as c_int))?;
+            (*frame).nb_samples = read;
+
+            Ok(read as usize)
+        }
+    }
+
+    /// Moves up to `samples` samples into a new frame, `None` if empty.
+    ///
+    /// The frame gets the channel layout of the last frame written.
+    pub fn read_frame(&mut self, samples: usize) -> Result<Option<AudioFrame>, AvError> {
+        let samples = cmp::min(samples, self.len());
+        if samples == 0 {
+            return Ok(None);
+        }
+
+        let mut frame = Frame::new();
+        unsafe {
+            let ptr = frame.as_mut_ptr();
+
+            (*ptr).format = self.format.0 as c_int;
+            (*ptr).channel_layout = self.layout.bits();
+            (*ptr).channels = self.channels as c_int;
+            (*ptr).nb_samples = samples as c_int;
+        }
+        frame.get_buffer(0)?;
+
+        let mut frame = AudioFrame::from(frame);
+        self.read_into(&mut frame, samples)?;
+
+        Ok(Some(frame))
+    }
+
+    /// Iterator reading frames of exactly `frame_size` samples while enough
+    /// are buffered; the remainder can be flushed with `read_frame`.
+    pub fn frames(&mut self, frame_size: usize) -> AudioFifoFrames<'_> {
+        AudioFifoFrames {
+            fifo: self,
+            frame_size,
+        }
+    }
+
+    /// Discards up to `samples` samples from the front.
+    pub fn drain(&mut self, samples: usize) {
+        let samples = cmp::min(samples, self.len());
+        unsafe { av_audio_fifo_drain(self.ptr, samples as c_int) };
+    }
+
+    /// Discards every sample.
+    pub fn reset(&mut self) {
+        unsafe { av_audio_fifo_reset(self.ptr) }
+    }
+
+    fn check(&self, frame: &AudioFrame) -> Result<(), AvError> {
+        if frame.format() != Some(self.format) || frame.channels() != self.channels {
+            Err(AvError(AVERROR(EINVAL)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for AudioFifo {
+    fn drop(&mut self) {
+        unsafe { av_audio_fifo_free(self.ptr) }
+    }
+}
+
+/// Frames of a fixed sample count read from an `AudioFifo`.
+pub struct AudioFifoFrames<'a> {
+    fifo: &'a mut AudioFifo,
+    frame_size: usize,
+}
+
+impl<'a> Iterator for AudioFifoFrames<'a> {
+    type Item = Result<AudioFrame, AvError>;
+
+    fn next(&mut self) -> Option<Result<AudioFrame, AvError>> {
+        if self.frame_size == 0 || self.fifo.len() < self.frame_size {
+            return None;
+        }
+
+        match self.fifo.read_frame(self.frame_size) {
+            Ok(frame) => frame.map(Ok),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
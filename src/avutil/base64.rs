@@ -0,0 +1,65 @@
+//! Base64 as in RFC 4648, with padding.
+
+use libc::{c_char, c_int, EINVAL};
+use {av_base64_decode, av_base64_encode, cstring, AvError, AVERROR};
+
+// A multiple of 3 bytes, so chunks encode without padding in between, and
+// small enough for the encoded chunk to fit in an `int`.
+const ENCODE_CHUNK: usize = 3 << 28;
+
+/// `AV_BASE64_SIZE`: the encoded length of `len` bytes, including the nul
+/// terminator FFmpeg writes.
+pub fn encoded_size(len: usize) -> usize {
+    (len + 2) / 3 * 4 + 1
+}
+
+/// `AV_BASE64_DECODE_SIZE`: an upper bound on the decoded length of `len`
+/// characters.
+pub fn decoded_size(len: usize) -> usize {
+    len / 4 * 3 + len % 4 * 3 / 4
+}
+
+pub fn encode(data: &[u8]) -> String {
+    let mut out = vec![0u8; encoded_size(data.len())];
+    let mut written = 0;
+
+    for chunk in data.chunks(ENCODE_CHUNK) {
+        let size = encoded_size(chunk.len());
+
+        unsafe {
+            let ret = av_base64_encode(
+                out[written..].as_mut_ptr() as *mut c_char,
+                size as c_int,
+                chunk.as_ptr(),
+                chunk.len() as c_int,
+            );
+            assert!(!ret.is_null(), "av_base64_encode: output buffer too small");
+        }
+
+        // Skip the nul terminator, the next chunk overwrites it
+        written += size - 1;
+    }
+
+    out.truncate(written);
+
+    String::from_utf8(out).expect("base64 output is ASCII")
+}
+
+/// Decodes `value`, failing with `AVERROR_INVALIDDATA` on characters
+/// outside the alphabet.
+pub fn decode(value: &str) -> Result<Vec<u8>, AvError> {
+    let size = decoded_size(value.len());
+    if size > c_int::max_value() as usize {
+        return Err(AvError(AVERROR(EINVAL)));
+    }
+
+    let value = cstring(value)?;
+    let mut out = vec![0u8; size];
+
+    let len = unsafe {
+        AvError::check(av_base64_decode(out.as_mut_ptr(), value.as_ptr(), size as c_int))?
+    };
+    out.truncate(len as usize);
+
+    Ok(out)
+}
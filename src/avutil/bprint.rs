@@ -0,0 +1,142 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::ptr;
+use std::slice;
+use std::str;
+
+use libc::{c_char, c_uint, c_void, ENOMEM};
+use {av_bprint_append_data, av_bprint_clear, av_bprint_finalize, av_bprint_init, av_free,
+     AVBPrint, AvError, AVERROR, AV_BPRINT_SIZE_AUTOMATIC};
+
+/// `((unsigned)-1)`, which bindgen does not evaluate.
+pub const AV_BPRINT_SIZE_UNLIMITED: c_uint = c_uint::max_value();
+
+/// A growing string buffer, as filled by FFmpeg's `*_bprint` functions.
+///
+/// Writes past the size limit are truncated rather than failing, which
+/// `is_complete` reports.
+pub struct BPrint {
+    // The buffer starts out in the struct itself, so it must not move
+    buf: Box<AVBPrint>,
+}
+
+unsafe impl Send for BPrint {}
+
+impl BPrint {
+    /// A buffer growing without limit.
+    pub fn new() -> Self {
+        BPrint::init(AV_BPRINT_SIZE_UNLIMITED)
+    }
+
+    /// A buffer that never allocates, truncating at the room inside
+    /// `AVBPrint`, about 1000 bytes.
+    pub fn automatic() -> Self {
+        BPrint::init(AV_BPRINT_SIZE_AUTOMATIC as c_uint)
+    }
+
+    fn init(size_max: c_uint) -> Self {
+        let mut buf = Box::new(unsafe { ::std::mem::zeroed() });
+        unsafe { av_bprint_init(&mut *buf, 0, size_max) };
+
+        BPrint { buf }
+    }
+
+    pub fn as_ptr(&self) -> *const AVBPrint {
+        &*self.buf
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVBPrint {
+        &mut *self.buf
+    }
+
+    /// Number of bytes written, including any that were truncated.
+    pub fn len(&self) -> usize {
+        self.buf.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.len == 0
+    }
+
+    /// Whether nothing was truncated, by the size limit or by a failed
+    /// allocation.
+    pub fn is_complete(&self) -> bool {
+        self.buf.len < self.buf.size
+    }
+
+    /// The bytes kept, excluding the nul terminator.
+    pub fn as_bytes(&self) -> &[u8] {
+        let len = if self.is_complete() {
+            self.buf.len
+        } else {
+            self.buf.size.saturating_sub(1)
+        };
+
+        if len == 0 {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(self.buf.str as *const u8, len as usize) }
+        }
+    }
+
+    pub fn as_str(&self) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(self.as_bytes())
+    }
+
+    /// Empties the buffer, keeping its allocation.
+    pub fn clear(&mut self) {
+        unsafe { av_bprint_clear(&mut *self.buf) }
+    }
+
+    /// Takes the contents, replacing invalid UTF-8 sequences.
+    pub fn into_string(mut self) -> Result<String, AvError> {
+        let mut ptr = ptr::null_mut();
+
+        unsafe {
+            AvError::check(av_bprint_finalize(&mut *self.buf, &mut ptr))?;
+            if ptr.is_null() {
+                return Err(AvError(AVERROR(ENOMEM)));
+            }
+
+            let string = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+            av_free(ptr as *mut c_void);
+
+            Ok(string)
+        }
+    }
+}
+
+impl Default for BPrint {
+    fn default() -> Self {
+        BPrint::new()
+    }
+}
+
+impl fmt::Write for BPrint {
+    /// Never fails, see `is_complete` for truncation.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for chunk in s.as_bytes().chunks(c_uint::max_value() as usize) {
+            unsafe {
+                av_bprint_append_data(
+                    &mut *self.buf,
+                    chunk.as_ptr() as *const c_char,
+                    chunk.len() as c_uint,
+                )
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BPrint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&String::from_utf8_lossy(self.as_bytes()), f)
+    }
+}
+
+impl Drop for BPrint {
+    fn drop(&mut self) {
+        unsafe { av_bprint_finalize(&mut *self.buf, ptr::null_mut()) };
+    }
+}
@@ -0,0 +1,297 @@
+use std::ops::Deref;
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice};
+
+use libc::{c_int, c_void, EINVAL, ENOMEM};
+use {av_buffer_alloc, av_buffer_allocz, av_buffer_create, av_buffer_get_ref_count,
+     av_buffer_is_writable, av_buffer_make_writable, av_buffer_pool_get, av_buffer_pool_init,
+     av_buffer_pool_init2, av_buffer_pool_uninit, av_buffer_realloc, av_buffer_ref,
+     av_buffer_unref, AVBufferPool, AVBufferRef, AvError, AVERROR};
+
+/// A reference to a reference counted `AVBuffer`.
+///
+/// Cloning adds a reference to the same data, which stays alive until the
+/// last reference is dropped.
+pub struct Buffer {
+    ptr: *mut AVBufferRef,
+}
+
+unsafe impl Send for Buffer {}
+unsafe impl Sync for Buffer {}
+
+impl Buffer {
+    pub fn new(size: usize) -> Result<Self, AvError> {
+        unsafe { Buffer::from_alloc(av_buffer_alloc(checked_size(size)?)) }
+    }
+
+    pub fn zeroed(size: usize) -> Result<Self, AvError> {
+        unsafe { Buffer::from_alloc(av_buffer_allocz(checked_size(size)?)) }
+    }
+
+    /// Wraps Rust-owned memory without copying it; `data` is dropped once
+    /// FFmpeg releases the last reference.
+    ///
+    /// Data handed to decoders must include `AV_INPUT_BUFFER_PADDING_SIZE`
+    /// zeroed bytes past the payload.
+    pub fn from_owned<T>(data: T) -> Result<Self, AvError>
+    where
+        T: AsMut<[u8]> + Send + 'static,
+    {
+        let mut owner = Box::new(data);
+        let (data, size) = {
+            let slice = (*owner).as_mut();
+            (slice.as_mut_ptr(), checked_size(slice.len())?)
+        };
+
+        unsafe {
+            let opaque = Box::into_raw(owner) as *mut c_void;
+            let ptr = av_buffer_create(data, size, Some(free_owned::<T>), opaque, 0);
+
+            if ptr.is_null() {
+                drop(Box::from_raw(opaque as *mut T));
+                Err(AvError(AVERROR(ENOMEM)))
+            } else {
+                Ok(Buffer { ptr })
+            }
+        }
+    }
+
+    pub fn from_vec(data: Vec<u8>) -> Result<Self, AvError> {
+        Buffer::from_owned(data)
+    }
+
+    pub fn from_boxed(data: Box<[u8]>) -> Result<Self, AvError> {
+        Buffer::from_owned(data)
+    }
+
+    /// Takes ownership of a reference.
+    pub unsafe fn from_raw(ptr: *mut AVBufferRef) -> Self {
+        Buffer { ptr }
+    }
+
+    /// Gives up ownership of the reference, e.g. to store it in `AVFrame.buf`.
+    pub fn into_raw(self) -> *mut AVBufferRef {
+        let ptr = self.ptr;
+        ::std::mem::forget(self);
+
+        ptr
+    }
+
+    pub fn as_ptr(&self) -> *const AVBufferRef {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVBufferRef {
+        self.ptr
+    }
+
+    /// Whether this is the only reference to the data, and it is not
+    /// read-only.
+    pub fn is_writable(&self) -> bool {
+        unsafe { av_buffer_is_writable(self.ptr) != 0 }
+    }
+
+    pub fn ref_count(&self) -> usize {
+        unsafe { av_buffer_get_ref_count(self.ptr) as usize }
+    }
+
+    /// Copies the data into a new buffer, unless it is already writable.
+    pub fn make_writable(&mut self) -> Result<(), AvError> {
+        unsafe { AvError::check(av_buffer_make_writable(&mut self.ptr)).map(|_| ()) }
+    }
+
+    /// Resizes the buffer, making it writable in the process.
+    pub fn realloc(&mut self, size: usize) -> Result<(), AvError> {
+        unsafe { AvError::check(av_buffer_realloc(&mut self.ptr, checked_size(size)?)).map(|_| ()) }
+    }
+
+    /// The data, when writable.
+    pub fn data_mut(&mut self) -> Option<&mut [u8]> {
+        unsafe {
+            if !self.is_writable() {
+                None
+            } else if (*self.ptr).size == 0 {
+                Some(&mut [])
+            } else {
+                Some(slice::from_raw_parts_mut((*self.ptr).data, (*self.ptr).size as usize))
+            }
+        }
+    }
+
+    unsafe fn from_alloc(ptr: *mut AVBufferRef) -> Result<Self, AvError> {
+        if ptr.is_null() {
+            Err(AvError(AVERROR(ENOMEM)))
+        } else {
+            Ok(Buffer { ptr })
+        }
+    }
+}
+
+impl Deref for Buffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe {
+            if (*self.ptr).size == 0 {
+                &[]
+            } else {
+                slice::from_raw_parts((*self.ptr).data, (*self.ptr).size as usize)
+            }
+        }
+    }
+}
+
+impl AsRef<[u8]> for Buffer {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Clone for Buffer {
+    fn clone(&self) -> Self {
+        let ptr = unsafe { av_buffer_ref(self.ptr) };
+        assert!(!ptr.is_null(), "av_buffer_ref: out of memory");
+
+        Buffer { ptr }
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe {
+            av_buffer_unref(&mut self.ptr);
+        }
+    }
+}
+
+unsafe extern "C" fn free_owned<T>(opaque: *mut c_void, _data: *mut u8) {
+    drop(Box::from_raw(opaque as *mut T));
+}
+
+fn checked_size(size: usize) -> Result<c_int, AvError> {
+    if size > c_int::max_value() as usize {
+        Err(AvError(AVERROR(EINVAL)))
+    } else {
+        Ok(size as c_int)
+    }
+}
+
+type Allocator = Box<dyn Fn(usize) -> Option<Buffer> + Send + Sync>;
+
+/// A pool of same-sized buffers, returned to the pool rather than freed
+/// when their last reference is dropped.
+///
+/// The pool itself is released once it has been dropped and every buffer
+/// handed out has been returned.
+pub struct BufferPool {
+    ptr: *mut AVBufferPool,
+}
+
+unsafe impl Send for BufferPool {}
+unsafe impl Sync for BufferPool {}
+
+impl BufferPool {
+    /// A pool allocating its buffers with `av_buffer_alloc`.
+    pub fn new(size: usize) -> Result<Self, AvError> {
+        let ptr = unsafe { av_buffer_pool_init(checked_size(size)?, None) };
+
+        if ptr.is_null() {
+            Err(AvError(AVERROR(ENOMEM)))
+        } else {
+            Ok(BufferPool { ptr })
+        }
+    }
+
+    /// A pool allocating its buffers with `alloc`, which is called with the
+    /// pool's buffer size and may hand out Rust-owned memory through
+    /// `Buffer::from_owned`. A buffer shorter than that size, or not
+    /// writable, e.g. because the allocator kept a reference to it, counts
+    /// as a failed allocation.
+    pub fn with_allocator<F>(size: usize, alloc: F) -> Result<Self, AvError>
+    where
+        F: Fn(usize) -> Option<Buffer> + Send + Sync + 'static,
+    {
+        let size = checked_size(size)?;
+        let alloc: Allocator = Box::new(alloc);
+
+        unsafe {
+            let opaque = Box::into_raw(Box::new(alloc)) as *mut c_void;
+            let ptr = av_buffer_pool_init2(size, opaque, Some(pool_alloc), Some(pool_free));
+
+            if ptr.is_null() {
+                pool_free(opaque);
+                Err(AvError(AVERROR(ENOMEM)))
+            } else {
+                Ok(BufferPool { ptr })
+            }
+        }
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVBufferPool {
+        self.ptr
+    }
+
+    /// A buffer from the pool, allocating a new one if none is free.
+    pub fn get(&self) -> Option<Buffer> {
+        unsafe {
+            let ptr = av_buffer_pool_get(self.ptr);
+
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Buffer { ptr })
+            }
+        }
+    }
+}
+
+impl Drop for BufferPool {
+    fn drop(&mut self) {
+        unsafe {
+            av_buffer_pool_uninit(&mut self.ptr);
+        }
+    }
+}
+
+unsafe extern "C" fn pool_alloc(opaque: *mut c_void, size: c_int) -> *mut AVBufferRef {
+    let alloc = &*(opaque as *const Allocator);
+
+    match panic::catch_unwind(AssertUnwindSafe(|| alloc(size as usize))) {
+        // FFmpeg uses the whole pool size, whatever the buffer's length, and
+        // hands the buffer out again and again as if it had no other owner
+        Ok(Some(buffer)) if buffer.len() >= size as usize && buffer.is_writable() => {
+            buffer.into_raw()
+        }
+        _ => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn pool_free(opaque: *mut c_void) {
+    drop(Box::from_raw(opaque as *mut Allocator));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn pool_rejects_short_and_shared_buffers() {
+        let pool = BufferPool::with_allocator(16, |size| Buffer::zeroed(size - 1).ok()).unwrap();
+        assert!(pool.get().is_none());
+
+        let kept = Mutex::new(Vec::new());
+        let pool = BufferPool::with_allocator(16, move |size| {
+            let buffer = Buffer::zeroed(size).ok()?;
+            kept.lock().unwrap().push(buffer.clone());
+            Some(buffer)
+        }).unwrap();
+        assert!(pool.get().is_none());
+
+        let pool = BufferPool::with_allocator(16, |size| Buffer::from_vec(vec![7; size]).ok())
+            .unwrap();
+        let buffer = pool.get().unwrap();
+        assert_eq!(&buffer[..], &[7; 16][..]);
+    }
+}
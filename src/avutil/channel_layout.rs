@@ -0,0 +1,186 @@
+use std::ffi::CString;
+use std::ptr;
+use std::str::FromStr;
+
+use libc::{c_int, c_uint, EINVAL};
+use {av_bprint_channel_layout, av_channel_layout_extract_channel, av_get_channel_description,
+     av_get_channel_layout, av_get_channel_layout_channel_index, av_get_channel_layout_nb_channels,
+     av_get_channel_name, av_get_default_channel_layout, av_get_standard_channel_layout,
+     ptr_to_str, AvError, BPrint, AVERROR};
+use {AV_CH_BACK_CENTER, AV_CH_BACK_LEFT, AV_CH_BACK_RIGHT, AV_CH_FRONT_CENTER, AV_CH_FRONT_LEFT,
+     AV_CH_FRONT_LEFT_OF_CENTER, AV_CH_FRONT_RIGHT, AV_CH_FRONT_RIGHT_OF_CENTER,
+     AV_CH_LAYOUT_2POINT1, AV_CH_LAYOUT_2_1, AV_CH_LAYOUT_2_2, AV_CH_LAYOUT_3POINT1,
+     AV_CH_LAYOUT_4POINT0, AV_CH_LAYOUT_4POINT1, AV_CH_LAYOUT_5POINT0, AV_CH_LAYOUT_5POINT0_BACK,
+     AV_CH_LAYOUT_5POINT1, AV_CH_LAYOUT_5POINT1_BACK, AV_CH_LAYOUT_6POINT0,
+     AV_CH_LAYOUT_6POINT0_FRONT, AV_CH_LAYOUT_6POINT1, AV_CH_LAYOUT_6POINT1_BACK,
+     AV_CH_LAYOUT_6POINT1_FRONT, AV_CH_LAYOUT_7POINT0, AV_CH_LAYOUT_7POINT0_FRONT,
+     AV_CH_LAYOUT_7POINT1, AV_CH_LAYOUT_7POINT1_WIDE, AV_CH_LAYOUT_7POINT1_WIDE_BACK,
+     AV_CH_LAYOUT_HEXAGONAL, AV_CH_LAYOUT_MONO, AV_CH_LAYOUT_NATIVE, AV_CH_LAYOUT_OCTAGONAL,
+     AV_CH_LAYOUT_QUAD, AV_CH_LAYOUT_STEREO, AV_CH_LAYOUT_STEREO_DOWNMIX, AV_CH_LAYOUT_SURROUND,
+     AV_CH_LOW_FREQUENCY, AV_CH_LOW_FREQUENCY_2, AV_CH_SIDE_LEFT, AV_CH_SIDE_RIGHT,
+     AV_CH_STEREO_LEFT, AV_CH_STEREO_RIGHT, AV_CH_SURROUND_DIRECT_LEFT,
+     AV_CH_SURROUND_DIRECT_RIGHT, AV_CH_TOP_BACK_CENTER, AV_CH_TOP_BACK_LEFT,
+     AV_CH_TOP_BACK_RIGHT, AV_CH_TOP_CENTER, AV_CH_TOP_FRONT_CENTER, AV_CH_TOP_FRONT_LEFT,
+     AV_CH_TOP_FRONT_RIGHT, AV_CH_WIDE_LEFT, AV_CH_WIDE_RIGHT};
+
+bitflags! {
+    /// A channel layout mask, as used by `AVCodecContext.channel_layout`
+    /// and `AVFrame.channel_layout`.
+    pub struct ChannelLayout: u64 {
+        const FRONT_LEFT            = AV_CH_FRONT_LEFT;
+        const FRONT_RIGHT           = AV_CH_FRONT_RIGHT;
+        const FRONT_CENTER          = AV_CH_FRONT_CENTER;
+        const LOW_FREQUENCY         = AV_CH_LOW_FREQUENCY;
+        const BACK_LEFT             = AV_CH_BACK_LEFT;
+        const BACK_RIGHT            = AV_CH_BACK_RIGHT;
+        const FRONT_LEFT_OF_CENTER  = AV_CH_FRONT_LEFT_OF_CENTER;
+        const FRONT_RIGHT_OF_CENTER = AV_CH_FRONT_RIGHT_OF_CENTER;
+        const BACK_CENTER           = AV_CH_BACK_CENTER;
+        const SIDE_LEFT             = AV_CH_SIDE_LEFT;
+        const SIDE_RIGHT            = AV_CH_SIDE_RIGHT;
+        const TOP_CENTER            = AV_CH_TOP_CENTER;
+        const TOP_FRONT_LEFT        = AV_CH_TOP_FRONT_LEFT;
+        const TOP_FRONT_CENTER      = AV_CH_TOP_FRONT_CENTER;
+        const TOP_FRONT_RIGHT       = AV_CH_TOP_FRONT_RIGHT;
+        const TOP_BACK_LEFT         = AV_CH_TOP_BACK_LEFT;
+        const TOP_BACK_CENTER       = AV_CH_TOP_BACK_CENTER;
+        const TOP_BACK_RIGHT        = AV_CH_TOP_BACK_RIGHT;
+        const STEREO_LEFT           = AV_CH_STEREO_LEFT;
+        const STEREO_RIGHT          = AV_CH_STEREO_RIGHT;
+        const WIDE_LEFT             = AV_CH_WIDE_LEFT;
+        const WIDE_RIGHT            = AV_CH_WIDE_RIGHT;
+        const SURROUND_DIRECT_LEFT  = AV_CH_SURROUND_DIRECT_LEFT;
+        const SURROUND_DIRECT_RIGHT = AV_CH_SURROUND_DIRECT_RIGHT;
+        const LOW_FREQUENCY_2       = AV_CH_LOW_FREQUENCY_2;
+        const NATIVE                = AV_CH_LAYOUT_NATIVE;
+
+        const MONO              = AV_CH_LAYOUT_MONO;
+        const STEREO            = AV_CH_LAYOUT_STEREO;
+        const _2POINT1          = AV_CH_LAYOUT_2POINT1;
+        const _2_1              = AV_CH_LAYOUT_2_1;
+        const SURROUND          = AV_CH_LAYOUT_SURROUND;
+        const _3POINT1          = AV_CH_LAYOUT_3POINT1;
+        const _4POINT0          = AV_CH_LAYOUT_4POINT0;
+        const _4POINT1          = AV_CH_LAYOUT_4POINT1;
+        const _2_2              = AV_CH_LAYOUT_2_2;
+        const QUAD              = AV_CH_LAYOUT_QUAD;
+        const _5POINT0          = AV_CH_LAYOUT_5POINT0;
+        const _5POINT1          = AV_CH_LAYOUT_5POINT1;
+        const _5POINT0_BACK     = AV_CH_LAYOUT_5POINT0_BACK;
+        const _5POINT1_BACK     = AV_CH_LAYOUT_5POINT1_BACK;
+        const _6POINT0          = AV_CH_LAYOUT_6POINT0;
+        const _6POINT0_FRONT    = AV_CH_LAYOUT_6POINT0_FRONT;
+        const HEXAGONAL         = AV_CH_LAYOUT_HEXAGONAL;
+        const _6POINT1          = AV_CH_LAYOUT_6POINT1;
+        const _6POINT1_BACK     = AV_CH_LAYOUT_6POINT1_BACK;
+        const _6POINT1_FRONT    = AV_CH_LAYOUT_6POINT1_FRONT;
+        const _7POINT0          = AV_CH_LAYOUT_7POINT0;
+        const _7POINT0_FRONT    = AV_CH_LAYOUT_7POINT0_FRONT;
+        const _7POINT1          = AV_CH_LAYOUT_7POINT1;
+        const _7POINT1_WIDE     = AV_CH_LAYOUT_7POINT1_WIDE;
+        const _7POINT1_WIDE_BACK = AV_CH_LAYOUT_7POINT1_WIDE_BACK;
+        const OCTAGONAL         = AV_CH_LAYOUT_OCTAGONAL;
+        const STEREO_DOWNMIX    = AV_CH_LAYOUT_STEREO_DOWNMIX;
+    }
+}
+
+impl ChannelLayout {
+    /// The default layout for the given number of channels, if there is one.
+    pub fn default_for(channels: usize) -> Option<ChannelLayout> {
+        match unsafe { av_get_default_channel_layout(channels as c_int) } {
+            0 => None,
+            layout => Some(ChannelLayout::from_bits_truncate(layout as u64)),
+        }
+    }
+
+    /// Parses a layout the way `ffmpeg -channel_layout` does: a standard
+    /// layout name (`"5.1"`), channel names joined by `+` (`"FL+FR"`), a
+    /// channel count (`"2c"`) or a raw mask.
+    pub fn from_string(value: &str) -> Option<ChannelLayout> {
+        let value = CString::new(value).ok()?;
+
+        match unsafe { av_get_channel_layout(value.as_ptr()) } {
+            0 => None,
+            layout => Some(ChannelLayout::from_bits_truncate(layout)),
+        }
+    }
+
+    /// The standard layouts known to FFmpeg along with their names.
+    pub fn standard() -> StandardLayouts {
+        StandardLayouts { index: 0 }
+    }
+
+    pub fn nb_channels(&self) -> usize {
+        unsafe { av_get_channel_layout_nb_channels(self.bits()) as usize }
+    }
+
+    /// Human readable description of the layout, e.g. `"5.1(side)"`.
+    pub fn describe(&self) -> String {
+        let mut buf = BPrint::new();
+
+        unsafe {
+            av_bprint_channel_layout(buf.as_mut_ptr(), self.nb_channels() as c_int, self.bits())
+        };
+
+        String::from_utf8_lossy(buf.as_bytes()).into_owned()
+    }
+
+    /// The `index`-th channel present in the layout.
+    pub fn channel(&self, index: usize) -> Option<ChannelLayout> {
+        match unsafe { av_channel_layout_extract_channel(self.bits(), index as c_int) } {
+            0 => None,
+            channel => Some(ChannelLayout::from_bits_truncate(channel)),
+        }
+    }
+
+    /// The index of a single `channel` within the layout.
+    pub fn index_of(&self, channel: ChannelLayout) -> Option<usize> {
+        match unsafe { av_get_channel_layout_channel_index(self.bits(), channel.bits()) } {
+            index if index < 0 => None,
+            index => Some(index as usize),
+        }
+    }
+
+    /// Short name of a single channel, e.g. `"FL"`.
+    pub fn channel_name(&self) -> Option<&'static str> {
+        unsafe { ptr_to_str(av_get_channel_name(self.bits())) }
+    }
+
+    /// Long description of a single channel, e.g. `"front left"`.
+    pub fn channel_description(&self) -> Option<&'static str> {
+        unsafe { ptr_to_str(av_get_channel_description(self.bits())) }
+    }
+}
+
+impl FromStr for ChannelLayout {
+    type Err = AvError;
+
+    fn from_str(value: &str) -> Result<Self, AvError> {
+        ChannelLayout::from_string(value).ok_or(AvError(AVERROR(EINVAL)))
+    }
+}
+
+pub struct StandardLayouts {
+    index: c_uint,
+}
+
+impl Iterator for StandardLayouts {
+    type Item = (ChannelLayout, &'static str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut layout = 0;
+        let mut name = ptr::null();
+
+        unsafe {
+            if av_get_standard_channel_layout(self.index, &mut layout, &mut name) < 0 {
+                return None;
+            }
+            self.index += 1;
+
+            Some((
+                ChannelLayout::from_bits_truncate(layout),
+                ptr_to_str(name).unwrap_or(""),
+            ))
+        }
+    }
+}
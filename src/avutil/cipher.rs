@@ -0,0 +1,491 @@
+use std::ptr;
+
+use libc::{c_int, c_void, EINVAL, ENOMEM};
+use {av_aes_alloc, av_aes_crypt, av_aes_init, av_blowfish_alloc, av_blowfish_crypt,
+     av_blowfish_init, av_camellia_alloc, av_camellia_crypt, av_camellia_init, av_cast5_alloc,
+     av_cast5_crypt2, av_cast5_init, av_free, av_twofish_alloc, av_twofish_crypt,
+     av_twofish_init, av_xtea_alloc, av_xtea_crypt, av_xtea_init, av_xtea_le_crypt,
+     av_xtea_le_init, AVBlowfish, AVAES, AVCAMELLIA, AVCAST5, AVTWOFISH, AVXTEA, AvError, AVERROR};
+
+/// A block cipher, in ECB mode or in CBC mode with a caller-held IV.
+///
+/// Input must be a whole number of blocks, no padding is applied. In CBC
+/// mode the IV is updated to chain into the next call, so a stream can be
+/// processed in pieces.
+pub trait BlockCipher {
+    /// Block size in bytes.
+    fn block_size(&self) -> usize;
+
+    /// Encrypts or decrypts `src` into `dst` of the same length, in CBC mode
+    /// if an IV is given.
+    fn crypt(
+        &mut self,
+        dst: &mut [u8],
+        src: &[u8],
+        iv: Option<&mut [u8]>,
+        decrypt: bool,
+    ) -> Result<(), AvError>;
+
+    fn crypt_in_place(
+        &mut self,
+        data: &mut [u8],
+        iv: Option<&mut [u8]>,
+        decrypt: bool,
+    ) -> Result<(), AvError>;
+
+    fn encrypt_ecb(&mut self, dst: &mut [u8], src: &[u8]) -> Result<(), AvError> {
+        self.crypt(dst, src, None, false)
+    }
+
+    fn decrypt_ecb(&mut self, dst: &mut [u8], src: &[u8]) -> Result<(), AvError> {
+        self.crypt(dst, src, None, true)
+    }
+
+    fn encrypt_cbc(&mut self, dst: &mut [u8], src: &[u8], iv: &mut [u8]) -> Result<(), AvError> {
+        self.crypt(dst, src, Some(iv), false)
+    }
+
+    fn decrypt_cbc(&mut self, dst: &mut [u8], src: &[u8], iv: &mut [u8]) -> Result<(), AvError> {
+        self.crypt(dst, src, Some(iv), true)
+    }
+}
+
+/// Number of blocks in `len` bytes, checking the IV is one block long.
+fn blocks(len: usize, block: usize, iv: &Option<&mut [u8]>) -> Result<c_int, AvError> {
+    let iv_ok = match *iv {
+        Some(ref iv) => iv.len() == block,
+        None => true,
+    };
+
+    if len % block != 0 || len / block > c_int::max_value() as usize || !iv_ok {
+        Err(AvError(AVERROR(EINVAL)))
+    } else {
+        Ok((len / block) as c_int)
+    }
+}
+
+macro_rules! block_cipher {
+	($name:ident, $block:expr) => {
+		impl BlockCipher for $name {
+			fn block_size(&self) -> usize {
+				$block
+			}
+
+			fn crypt(
+				&mut self,
+				dst: &mut [u8],
+				src: &[u8],
+				iv: Option<&mut [u8]>,
+				decrypt: bool,
+			) -> Result<(), AvError> {
+				if dst.len() != src.len() {
+					return Err(AvError(AVERROR(EINVAL)));
+				}
+				let count = blocks(src.len(), $block, &iv)?;
+				let iv = iv.map_or(ptr::null_mut(), |iv| iv.as_mut_ptr());
+
+				unsafe { self.crypt_blocks(dst.as_mut_ptr(), src.as_ptr(), count, iv, decrypt) }
+
+				Ok(())
+			}
+
+			fn crypt_in_place(
+				&mut self,
+				data: &mut [u8],
+				iv: Option<&mut [u8]>,
+				decrypt: bool,
+			) -> Result<(), AvError> {
+				let count = blocks(data.len(), $block, &iv)?;
+				let iv = iv.map_or(ptr::null_mut(), |iv| iv.as_mut_ptr());
+				let data = data.as_mut_ptr();
+
+				unsafe { self.crypt_blocks(data, data, count, iv, decrypt) }
+
+				Ok(())
+			}
+		}
+
+		unsafe impl Send for $name {}
+	};
+}
+
+macro_rules! alloc {
+	($alloc:ident) => {{
+		let ptr = unsafe { $alloc() };
+		if ptr.is_null() {
+			return Err(AvError(AVERROR(ENOMEM)));
+		}
+
+		ptr
+	}};
+}
+
+fn key_bits(key: &[u8], valid: bool) -> Result<c_int, AvError> {
+    if valid {
+        Ok(key.len() as c_int * 8)
+    } else {
+        Err(AvError(AVERROR(EINVAL)))
+    }
+}
+
+/// AES (Rijndael) with a 128, 192 or 256-bit key.
+pub struct Aes {
+    encrypt: *mut AVAES,
+    decrypt: *mut AVAES,
+}
+
+impl Aes {
+    pub fn new(key: &[u8]) -> Result<Self, AvError> {
+        let bits = key_bits(key, key.len() == 16 || key.len() == 24 || key.len() == 32)?;
+        let mut aes = Aes {
+            encrypt: ptr::null_mut(),
+            decrypt: ptr::null_mut(),
+        };
+        aes.encrypt = alloc!(av_aes_alloc);
+        aes.decrypt = alloc!(av_aes_alloc);
+
+        unsafe {
+            AvError::check(av_aes_init(aes.encrypt, key.as_ptr(), bits, 0))?;
+            AvError::check(av_aes_init(aes.decrypt, key.as_ptr(), bits, 1))?;
+        }
+
+        Ok(aes)
+    }
+
+    unsafe fn crypt_blocks(
+        &mut self,
+        dst: *mut u8,
+        src: *const u8,
+        count: c_int,
+        iv: *mut u8,
+        decrypt: bool,
+    ) {
+        let ctx = if decrypt { self.decrypt } else { self.encrypt };
+        av_aes_crypt(ctx, dst, src, count, iv, decrypt as c_int)
+    }
+}
+
+block_cipher!(Aes, 16);
+
+impl Drop for Aes {
+    fn drop(&mut self) {
+        unsafe {
+            av_free(self.encrypt as *mut c_void);
+            av_free(self.decrypt as *mut c_void);
+        }
+    }
+}
+
+/// Blowfish with a key of 1 to 56 bytes.
+pub struct Blowfish {
+    ptr: *mut AVBlowfish,
+}
+
+impl Blowfish {
+    pub fn new(key: &[u8]) -> Result<Self, AvError> {
+        key_bits(key, !key.is_empty() && key.len() <= 56)?;
+        let blowfish = Blowfish {
+            ptr: alloc!(av_blowfish_alloc),
+        };
+
+        unsafe { av_blowfish_init(blowfish.ptr, key.as_ptr(), key.len() as c_int) }
+
+        Ok(blowfish)
+    }
+
+    unsafe fn crypt_blocks(
+        &mut self,
+        dst: *mut u8,
+        src: *const u8,
+        count: c_int,
+        iv: *mut u8,
+        decrypt: bool,
+    ) {
+        av_blowfish_crypt(self.ptr, dst, src, count, iv, decrypt as c_int)
+    }
+}
+
+block_cipher!(Blowfish, 8);
+
+impl Drop for Blowfish {
+    fn drop(&mut self) {
+        unsafe { av_free(self.ptr as *mut c_void) }
+    }
+}
+
+/// Camellia with a 128, 192 or 256-bit key.
+pub struct Camellia {
+    ptr: *mut AVCAMELLIA,
+}
+
+impl Camellia {
+    pub fn new(key: &[u8]) -> Result<Self, AvError> {
+        let bits = key_bits(key, key.len() == 16 || key.len() == 24 || key.len() == 32)?;
+        let camellia = Camellia {
+            ptr: alloc!(av_camellia_alloc),
+        };
+
+        unsafe { AvError::check(av_camellia_init(camellia.ptr, key.as_ptr(), bits))? };
+
+        Ok(camellia)
+    }
+
+    unsafe fn crypt_blocks(
+        &mut self,
+        dst: *mut u8,
+        src: *const u8,
+        count: c_int,
+        iv: *mut u8,
+        decrypt: bool,
+    ) {
+        av_camellia_crypt(self.ptr, dst, src, count, iv, decrypt as c_int)
+    }
+}
+
+block_cipher!(Camellia, 16);
+
+impl Drop for Camellia {
+    fn drop(&mut self) {
+        unsafe { av_free(self.ptr as *mut c_void) }
+    }
+}
+
+/// CAST-128 (CAST5) with a key of 5 to 16 bytes.
+pub struct Cast5 {
+    ptr: *mut AVCAST5,
+}
+
+impl Cast5 {
+    pub fn new(key: &[u8]) -> Result<Self, AvError> {
+        let bits = key_bits(key, key.len() >= 5 && key.len() <= 16)?;
+        let cast5 = Cast5 {
+            ptr: alloc!(av_cast5_alloc),
+        };
+
+        unsafe { AvError::check(av_cast5_init(cast5.ptr, key.as_ptr(), bits))? };
+
+        Ok(cast5)
+    }
+
+    unsafe fn crypt_blocks(
+        &mut self,
+        dst: *mut u8,
+        src: *const u8,
+        count: c_int,
+        iv: *mut u8,
+        decrypt: bool,
+    ) {
+        av_cast5_crypt2(self.ptr, dst, src, count, iv, decrypt as c_int)
+    }
+}
+
+block_cipher!(Cast5, 8);
+
+impl Drop for Cast5 {
+    fn drop(&mut self) {
+        unsafe { av_free(self.ptr as *mut c_void) }
+    }
+}
+
+/// Twofish with a 128, 192 or 256-bit key.
+pub struct Twofish {
+    ptr: *mut AVTWOFISH,
+}
+
+impl Twofish {
+    pub fn new(key: &[u8]) -> Result<Self, AvError> {
+        let bits = key_bits(key, key.len() == 16 || key.len() == 24 || key.len() == 32)?;
+        let twofish = Twofish {
+            ptr: alloc!(av_twofish_alloc),
+        };
+
+        unsafe { AvError::check(av_twofish_init(twofish.ptr, key.as_ptr(), bits))? };
+
+        Ok(twofish)
+    }
+
+    unsafe fn crypt_blocks(
+        &mut self,
+        dst: *mut u8,
+        src: *const u8,
+        count: c_int,
+        iv: *mut u8,
+        decrypt: bool,
+    ) {
+        av_twofish_crypt(self.ptr, dst, src, count, iv, decrypt as c_int)
+    }
+}
+
+block_cipher!(Twofish, 16);
+
+impl Drop for Twofish {
+    fn drop(&mut self) {
+        unsafe { av_free(self.ptr as *mut c_void) }
+    }
+}
+
+/// XTEA with a 128-bit key, reading words big endian as in the reference
+/// implementation, or little endian.
+pub struct Xtea {
+    ptr: *mut AVXTEA,
+    little_endian: bool,
+}
+
+impl Xtea {
+    pub fn new(key: &[u8]) -> Result<Self, AvError> {
+        Xtea::init(key, false)
+    }
+
+    pub fn new_le(key: &[u8]) -> Result<Self, AvError> {
+        Xtea::init(key, true)
+    }
+
+    fn init(key: &[u8], little_endian: bool) -> Result<Self, AvError> {
+        key_bits(key, key.len() == 16)?;
+        let xtea = Xtea {
+            ptr: alloc!(av_xtea_alloc),
+            little_endian,
+        };
+
+        unsafe {
+            if little_endian {
+                av_xtea_le_init(xtea.ptr, key.as_ptr())
+            } else {
+                av_xtea_init(xtea.ptr, key.as_ptr())
+            }
+        }
+
+        Ok(xtea)
+    }
+
+    unsafe fn crypt_blocks(
+        &mut self,
+        dst: *mut u8,
+        src: *const u8,
+        count: c_int,
+        iv: *mut u8,
+        decrypt: bool,
+    ) {
+        if self.little_endian {
+            av_xtea_le_crypt(self.ptr, dst, src, count, iv, decrypt as c_int)
+        } else {
+            av_xtea_crypt(self.ptr, dst, src, count, iv, decrypt as c_int)
+        }
+    }
+}
+
+block_cipher!(Xtea, 8);
+
+impl Drop for Xtea {
+    fn drop(&mut self) {
+        unsafe { av_free(self.ptr as *mut c_void) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn check<C: BlockCipher>(mut cipher: C, plain: &str, encrypted: &str) {
+        let (plain, encrypted) = (bytes(plain), bytes(encrypted));
+        let mut out = vec![0; plain.len()];
+
+        cipher.encrypt_ecb(&mut out, &plain).unwrap();
+        assert_eq!(out, encrypted);
+
+        cipher.crypt_in_place(&mut out, None, true).unwrap();
+        assert_eq!(out, plain);
+    }
+
+    // FIPS-197, appendix C
+    #[test]
+    fn aes() {
+        let plain = "00112233445566778899aabbccddeeff";
+        let key = bytes("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+
+        check(Aes::new(&key[..16]).unwrap(), plain, "69c4e0d86a7b0430d8cdb78070b4c55a");
+        check(Aes::new(&key[..24]).unwrap(), plain, "dda97ca4864cdfe06eaf70a0ec0d7191");
+        check(Aes::new(&key).unwrap(), plain, "8ea2b7ca516745bfeafc49904b496089");
+        assert!(Aes::new(&key[..20]).is_err());
+    }
+
+    // SP 800-38A, F.2.1 and F.2.2, split across calls to chain the IV
+    #[test]
+    fn aes_cbc() {
+        let mut aes = Aes::new(&bytes("2b7e151628aed2a6abf7158809cf4f3c")).unwrap();
+        let plain = bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        let encrypted =
+            bytes("7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2");
+        let mut out = vec![0; plain.len()];
+
+        let mut iv = bytes("000102030405060708090a0b0c0d0e0f");
+        aes.encrypt_cbc(&mut out[..16], &plain[..16], &mut iv).unwrap();
+        aes.encrypt_cbc(&mut out[16..], &plain[16..], &mut iv).unwrap();
+        assert_eq!(out, encrypted);
+
+        let mut iv = bytes("000102030405060708090a0b0c0d0e0f");
+        aes.crypt_in_place(&mut out, Some(&mut iv), true).unwrap();
+        assert_eq!(out, plain);
+
+        assert!(aes.encrypt_cbc(&mut out[..8], &plain[..8], &mut iv).is_err());
+        assert!(aes.encrypt_cbc(&mut out[..16], &plain[..16], &mut iv[..8]).is_err());
+    }
+
+    // RFC 3713, appendix A
+    #[test]
+    fn camellia() {
+        let plain = "0123456789abcdeffedcba9876543210";
+        let key = bytes("0123456789abcdeffedcba987654321000112233445566778899aabbccddeeff");
+
+        check(Camellia::new(&key[..16]).unwrap(), plain, "67673138549669730857065648eabe43");
+        check(Camellia::new(&key[..24]).unwrap(), plain, "b4993401b3e996f84ee5cee7d79b09b9");
+        check(Camellia::new(&key).unwrap(), plain, "9acc237dff16d76c20ef7c919e3a7509");
+    }
+
+    // RFC 2144, appendix B.1
+    #[test]
+    fn cast5() {
+        let plain = "0123456789abcdef";
+        let key = bytes("0123456712345678234567893456789a");
+
+        check(Cast5::new(&key).unwrap(), plain, "238b4fe5847e44b2");
+        check(Cast5::new(&key[..10]).unwrap(), plain, "eb6a711a2c02271b");
+        check(Cast5::new(&key[..5]).unwrap(), plain, "7ac816d16e9b302e");
+        assert!(Cast5::new(&key[..4]).is_err());
+    }
+
+    // Eric Young's test vectors
+    #[test]
+    fn blowfish() {
+        let blowfish = |key| Blowfish::new(&bytes(key)).unwrap();
+
+        check(blowfish("0000000000000000"), "0000000000000000", "4ef997456198dd78");
+        check(blowfish("ffffffffffffffff"), "ffffffffffffffff", "51866fd5b85ecb8a");
+        check(blowfish("3000000000000000"), "1000000000000001", "7d856f9a613063f2");
+    }
+
+    #[test]
+    fn xtea() {
+        let xtea = |key| Xtea::new(&bytes(key)).unwrap();
+
+        check(xtea("000102030405060708090a0b0c0d0e0f"), "4142434445464748", "497df3d072612cb5");
+        check(xtea("00000000000000000000000000000000"), "4142434445464748", "a0390589f8b8efa5");
+    }
+
+    // The Twofish paper, ECB_TBL.TXT
+    #[test]
+    fn twofish() {
+        let plain = "00000000000000000000000000000000";
+        let key = [0; 32];
+
+        check(Twofish::new(&key[..16]).unwrap(), plain, "9f589f5cf6122c32b6bfec2f2ae8c35a");
+        check(Twofish::new(&key).unwrap(), plain, "57ff739d4dc92c1bd7fc01700cc8216f");
+    }
+}
@@ -0,0 +1,107 @@
+use libc::{c_int, c_uint};
+use {av_cpu_count, av_cpu_max_align, av_force_cpu_flags, av_get_cpu_flags, av_parse_cpu_caps,
+     cstring, AvError};
+use {AV_CPU_FLAG_3DNOW, AV_CPU_FLAG_3DNOWEXT, AV_CPU_FLAG_AESNI, AV_CPU_FLAG_ALTIVEC,
+     AV_CPU_FLAG_ARMV5TE, AV_CPU_FLAG_ARMV6, AV_CPU_FLAG_ARMV6T2, AV_CPU_FLAG_ARMV8,
+     AV_CPU_FLAG_ATOM, AV_CPU_FLAG_AVX, AV_CPU_FLAG_AVX2, AV_CPU_FLAG_AVXSLOW, AV_CPU_FLAG_BMI1,
+     AV_CPU_FLAG_BMI2, AV_CPU_FLAG_CMOV, AV_CPU_FLAG_FMA3, AV_CPU_FLAG_FMA4, AV_CPU_FLAG_MMX,
+     AV_CPU_FLAG_MMXEXT, AV_CPU_FLAG_NEON, AV_CPU_FLAG_POWER8, AV_CPU_FLAG_SETEND,
+     AV_CPU_FLAG_SSE, AV_CPU_FLAG_SSE2, AV_CPU_FLAG_SSE2SLOW, AV_CPU_FLAG_SSE3,
+     AV_CPU_FLAG_SSE3SLOW, AV_CPU_FLAG_SSE4, AV_CPU_FLAG_SSE42, AV_CPU_FLAG_SSSE3,
+     AV_CPU_FLAG_SSSE3SLOW, AV_CPU_FLAG_VFP, AV_CPU_FLAG_VFPV3, AV_CPU_FLAG_VFP_VM,
+     AV_CPU_FLAG_VSX, AV_CPU_FLAG_XOP};
+
+bitflags! {
+    /// SIMD extensions FFmpeg can use.
+    ///
+    /// Bits are reused across architectures, e.g. `MMX`, `ALTIVEC` and
+    /// `ARMV5TE` are the same bit, so only the flags of the target
+    /// architecture are meaningful.
+    pub struct CpuFlags: c_int {
+        // x86
+        const MMX       = AV_CPU_FLAG_MMX;
+        const MMXEXT    = AV_CPU_FLAG_MMXEXT;
+        const _3DNOW    = AV_CPU_FLAG_3DNOW;
+        const SSE       = AV_CPU_FLAG_SSE;
+        const SSE2      = AV_CPU_FLAG_SSE2;
+        /// SSE2 is supported but usually not faster than MMX.
+        const SSE2SLOW  = AV_CPU_FLAG_SSE2SLOW;
+        const _3DNOWEXT = AV_CPU_FLAG_3DNOWEXT;
+        const SSE3      = AV_CPU_FLAG_SSE3;
+        const SSE3SLOW  = AV_CPU_FLAG_SSE3SLOW;
+        const SSSE3     = AV_CPU_FLAG_SSSE3;
+        const SSSE3SLOW = AV_CPU_FLAG_SSSE3SLOW;
+        /// Atom processor, some SSSE3 instructions are slower.
+        const ATOM      = AV_CPU_FLAG_ATOM;
+        const SSE4      = AV_CPU_FLAG_SSE4;
+        const SSE42     = AV_CPU_FLAG_SSE42;
+        const AESNI     = AV_CPU_FLAG_AESNI;
+        const AVX       = AV_CPU_FLAG_AVX;
+        /// AVX is supported but slow, e.g. on Bulldozer.
+        const AVXSLOW   = AV_CPU_FLAG_AVXSLOW;
+        const XOP       = AV_CPU_FLAG_XOP;
+        const FMA4      = AV_CPU_FLAG_FMA4;
+        const CMOV      = AV_CPU_FLAG_CMOV;
+        const AVX2      = AV_CPU_FLAG_AVX2;
+        const FMA3      = AV_CPU_FLAG_FMA3;
+        const BMI1      = AV_CPU_FLAG_BMI1;
+        const BMI2      = AV_CPU_FLAG_BMI2;
+
+        // PowerPC
+        const ALTIVEC   = AV_CPU_FLAG_ALTIVEC;
+        const VSX       = AV_CPU_FLAG_VSX;
+        const POWER8    = AV_CPU_FLAG_POWER8;
+
+        // ARM and AArch64
+        const ARMV5TE   = AV_CPU_FLAG_ARMV5TE;
+        const ARMV6     = AV_CPU_FLAG_ARMV6;
+        const ARMV6T2   = AV_CPU_FLAG_ARMV6T2;
+        const VFP       = AV_CPU_FLAG_VFP;
+        const VFPV3     = AV_CPU_FLAG_VFPV3;
+        const NEON      = AV_CPU_FLAG_NEON;
+        const ARMV8     = AV_CPU_FLAG_ARMV8;
+        /// VFP in vector mode, only used in code built for it.
+        const VFP_VM    = AV_CPU_FLAG_VFP_VM;
+        const SETEND    = AV_CPU_FLAG_SETEND;
+    }
+}
+
+/// The flags FFmpeg uses, as detected or as last forced.
+pub fn cpu_flags() -> CpuFlags {
+    CpuFlags::from_bits_truncate(unsafe { av_get_cpu_flags() })
+}
+
+/// Makes FFmpeg use only the given extensions, or detect them again with
+/// `None`.
+///
+/// Affects the whole process, and code paths are picked when contexts are
+/// initialized, so this must be called before opening codecs or filters to
+/// take effect. Enabling flags the CPU lacks leads to illegal instructions.
+pub fn force_cpu_flags(flags: Option<CpuFlags>) {
+    unsafe { av_force_cpu_flags(flags.map_or(-1, |flags| flags.bits())) }
+}
+
+/// Applies a list of extensions such as `"sse2+avx"`, `"-avx2"` or `"0"` on
+/// top of `flags`, as the `-cpuflags` option of the ffmpeg tool does.
+///
+/// A name prefixed with `+` or nothing enables it and everything it implies,
+/// `-` disables it.
+pub fn parse_cpu_caps(flags: CpuFlags, caps: &str) -> Result<CpuFlags, AvError> {
+    let caps = cstring(caps)?;
+    let mut flags = flags.bits() as c_uint;
+
+    unsafe { AvError::check(av_parse_cpu_caps(&mut flags, caps.as_ptr()))? };
+
+    Ok(CpuFlags::from_bits_truncate(flags as c_int))
+}
+
+/// Number of logical CPU cores.
+pub fn cpu_count() -> usize {
+    unsafe { av_cpu_count() as usize }
+}
+
+/// Alignment in bytes that the widest enabled SIMD extension needs, e.g. 32
+/// with AVX2 or 16 with SSE and NEON.
+pub fn cpu_max_align() -> usize {
+    unsafe { av_cpu_max_align() as usize }
+}
@@ -0,0 +1,379 @@
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ptr;
+use std::str::FromStr;
+
+use libc::{c_char, c_int, c_void, EINVAL};
+use {av_dict_copy, av_dict_count, av_dict_free, av_dict_get, av_dict_get_string,
+     av_dict_parse_string, av_dict_set, av_free, cstring, AVDictionary, AVDictionaryEntry,
+     AvError, AVERROR};
+use {AV_DICT_APPEND, AV_DICT_DONT_OVERWRITE, AV_DICT_IGNORE_SUFFIX, AV_DICT_MATCH_CASE,
+     AV_DICT_MULTIKEY};
+
+bitflags! {
+    pub struct DictionaryFlags: c_int {
+        const MATCH_CASE     = AV_DICT_MATCH_CASE;
+        const IGNORE_SUFFIX  = AV_DICT_IGNORE_SUFFIX;
+        const DONT_OVERWRITE = AV_DICT_DONT_OVERWRITE;
+        const APPEND         = AV_DICT_APPEND;
+        const MULTIKEY       = AV_DICT_MULTIKEY;
+    }
+}
+
+/// An `AVDictionary` borrowed from FFmpeg, e.g. `AVFormatContext.metadata`.
+///
+/// A null pointer is a valid, empty dictionary.
+#[derive(Copy, Clone)]
+pub struct DictionaryRef<'a> {
+    ptr: *const AVDictionary,
+    _marker: PhantomData<&'a AVDictionary>,
+}
+
+impl<'a> DictionaryRef<'a> {
+    pub unsafe fn wrap(ptr: *const AVDictionary) -> Self {
+        DictionaryRef {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const AVDictionary {
+        self.ptr
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { av_dict_count(self.ptr) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Case insensitive lookup of `key`.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.get_with(key, DictionaryFlags::empty())
+    }
+
+    /// Looks up `key`, honouring `MATCH_CASE` and `IGNORE_SUFFIX`.
+    pub fn get_with(&self, key: &str, flags: DictionaryFlags) -> Option<&'a str> {
+        let key = CString::new(key).ok()?;
+
+        unsafe {
+            let entry = av_dict_get(self.ptr, key.as_ptr(), ptr::null(), flags.bits());
+
+            if entry.is_null() {
+                None
+            } else {
+                CStr::from_ptr((*entry).value).to_str().ok()
+            }
+        }
+    }
+
+    /// Iterates over all entries in insertion order, skipping those whose
+    /// key or value is not valid UTF-8.
+    pub fn iter(&self) -> DictionaryIter<'a> {
+        DictionaryIter {
+            ptr: self.ptr,
+            cur: ptr::null(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Serializes the dictionary with `av_dict_get_string`, escaping the
+    /// separators where they appear in keys or values.
+    pub fn to_string_with(&self, key_val_sep: char, pairs_sep: char) -> Result<String, AvError> {
+        if !key_val_sep.is_ascii() || !pairs_sep.is_ascii() {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        unsafe {
+            let mut buffer: *mut c_char = ptr::null_mut();
+
+            AvError::check(av_dict_get_string(
+                self.ptr,
+                &mut buffer,
+                key_val_sep as c_char,
+                pairs_sep as c_char,
+            ))?;
+
+            let string = CStr::from_ptr(buffer).to_string_lossy().into_owned();
+            av_free(buffer as *mut c_void);
+
+            Ok(string)
+        }
+    }
+
+    /// A copy of every entry, failing rather than truncating the copy if
+    /// memory runs out.
+    pub fn try_to_dictionary(&self) -> Result<Dictionary, AvError> {
+        let mut dictionary = Dictionary::new();
+
+        unsafe {
+            AvError::check(av_dict_copy(&mut dictionary.ptr, self.ptr, 0))?;
+        }
+
+        Ok(dictionary)
+    }
+}
+
+impl<'a> IntoIterator for DictionaryRef<'a> {
+    type Item = (&'a str, &'a str);
+    type IntoIter = DictionaryIter<'a>;
+
+    fn into_iter(self) -> DictionaryIter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> fmt::Debug for DictionaryRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+pub struct DictionaryIter<'a> {
+    ptr: *const AVDictionary,
+    cur: *const AVDictionaryEntry,
+    _marker: PhantomData<&'a AVDictionary>,
+}
+
+impl<'a> Iterator for DictionaryIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            loop {
+                let empty = b"\0".as_ptr() as *const c_char;
+                let entry = av_dict_get(self.ptr, empty, self.cur, AV_DICT_IGNORE_SUFFIX);
+
+                if entry.is_null() {
+                    return None;
+                }
+                self.cur = entry;
+
+                let key = CStr::from_ptr((*entry).key).to_str();
+                let value = CStr::from_ptr((*entry).value).to_str();
+
+                if let (Ok(key), Ok(value)) = (key, value) {
+                    return Some((key, value));
+                }
+            }
+        }
+    }
+}
+
+/// An owned `AVDictionary`, freed with `av_dict_free` on drop.
+pub struct Dictionary {
+    ptr: *mut AVDictionary,
+}
+
+unsafe impl Send for Dictionary {}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        Dictionary {
+            ptr: ptr::null_mut(),
+        }
+    }
+
+    /// Takes ownership of a dictionary allocated by FFmpeg.
+    pub unsafe fn from_raw(ptr: *mut AVDictionary) -> Self {
+        Dictionary { ptr }
+    }
+
+    /// Gives up ownership, e.g. to assign to `AVStream.metadata`.
+    pub fn into_raw(self) -> *mut AVDictionary {
+        let ptr = self.ptr;
+        ::std::mem::forget(self);
+
+        ptr
+    }
+
+    pub fn as_ptr(&self) -> *const AVDictionary {
+        self.ptr
+    }
+
+    /// For functions taking an `AVDictionary **`, such as `avcodec_open2`,
+    /// which may replace the dictionary with the unused entries.
+    pub fn as_mut_ptr(&mut self) -> *mut *mut AVDictionary {
+        &mut self.ptr
+    }
+
+    pub fn borrowed(&self) -> DictionaryRef<'_> {
+        unsafe { DictionaryRef::wrap(self.ptr) }
+    }
+
+    /// Parses `key=value:key2=value2` style strings.
+    pub fn parse(value: &str, key_val_sep: &str, pairs_sep: &str) -> Result<Self, AvError> {
+        let mut dictionary = Dictionary::new();
+        dictionary.parse_into(value, key_val_sep, pairs_sep, DictionaryFlags::empty())?;
+
+        Ok(dictionary)
+    }
+
+    /// Parses entries into this dictionary, where each character of the
+    /// separator strings is accepted as a separator.
+    pub fn parse_into(
+        &mut self,
+        value: &str,
+        key_val_sep: &str,
+        pairs_sep: &str,
+        flags: DictionaryFlags,
+    ) -> Result<(), AvError> {
+        let value = cstring(value)?;
+        let key_val_sep = cstring(key_val_sep)?;
+        let pairs_sep = cstring(pairs_sep)?;
+
+        unsafe {
+            AvError::check(av_dict_parse_string(
+                &mut self.ptr,
+                value.as_ptr(),
+                key_val_sep.as_ptr(),
+                pairs_sep.as_ptr(),
+                flags.bits(),
+            )).map(|_| ())
+        }
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), AvError> {
+        self.set_with(key, value, DictionaryFlags::empty())
+    }
+
+    pub fn set_with(&mut self, key: &str, value: &str, flags: DictionaryFlags) -> Result<(), AvError> {
+        let key = cstring(key)?;
+        let value = cstring(value)?;
+
+        unsafe {
+            AvError::check(av_dict_set(
+                &mut self.ptr,
+                key.as_ptr(),
+                value.as_ptr(),
+                flags.bits(),
+            )).map(|_| ())
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Result<(), AvError> {
+        let key = cstring(key)?;
+
+        unsafe {
+            AvError::check(av_dict_set(
+                &mut self.ptr,
+                key.as_ptr(),
+                ptr::null(),
+                0,
+            )).map(|_| ())
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.borrowed().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.borrowed().is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.borrowed().get(key)
+    }
+
+    pub fn get_with(&self, key: &str, flags: DictionaryFlags) -> Option<&str> {
+        self.borrowed().get_with(key, flags)
+    }
+
+    pub fn iter(&self) -> DictionaryIter<'_> {
+        self.borrowed().iter()
+    }
+
+    pub fn to_string_with(&self, key_val_sep: char, pairs_sep: char) -> Result<String, AvError> {
+        self.borrowed().to_string_with(key_val_sep, pairs_sep)
+    }
+
+    /// Sets every entry, stopping at the first one FFmpeg refuses.
+    pub fn extend_from<I, K, V>(&mut self, iter: I) -> Result<(), AvError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (key, value) in iter {
+            self.set(key.as_ref(), value.as_ref())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Dictionary::new()
+    }
+}
+
+impl Drop for Dictionary {
+    fn drop(&mut self) {
+        unsafe {
+            av_dict_free(&mut self.ptr);
+        }
+    }
+}
+
+impl Clone for Dictionary {
+    fn clone(&self) -> Self {
+        self.borrowed().try_to_dictionary().expect("av_dict_copy: out of memory")
+    }
+}
+
+impl fmt::Debug for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.borrowed().fmt(f)
+    }
+}
+
+/// Serializes as `key=value:key2=value2`.
+impl fmt::Display for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let string = self.to_string_with('=', ':').map_err(|_| fmt::Error)?;
+        f.write_str(&string)
+    }
+}
+
+/// Parses `key=value:key2=value2`.
+impl FromStr for Dictionary {
+    type Err = AvError;
+
+    fn from_str(value: &str) -> Result<Self, AvError> {
+        Dictionary::parse(value, "=", ":")
+    }
+}
+
+/// Entries FFmpeg refuses, such as keys containing a nul byte, are skipped;
+/// `extend_from` reports them instead.
+impl<K: AsRef<str>, V: AsRef<str>> Extend<(K, V)> for Dictionary {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            let _ = self.set(key.as_ref(), value.as_ref());
+        }
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for Dictionary {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut dictionary = Dictionary::new();
+        dictionary.extend(iter);
+
+        dictionary
+    }
+}
+
+impl<'a> IntoIterator for &'a Dictionary {
+    type Item = (&'a str, &'a str);
+    type IntoIter = DictionaryIter<'a>;
+
+    fn into_iter(self) -> DictionaryIter<'a> {
+        self.iter()
+    }
+}
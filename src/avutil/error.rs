@@ -1,4 +1,9 @@
+use std::error;
+use std::ffi::CStr;
+use std::fmt;
+
 use libc::{c_char, c_int, size_t};
+use AV_ERROR_MAX_STRING_SIZE;
 
 #[inline(always)]
 pub fn AVERROR(e: c_int) -> c_int {
@@ -57,3 +62,41 @@ pub unsafe fn av_make_error_string(
 extern "C" {
     pub fn av_strerror(errnum: c_int, errbuf: *mut c_char, errbuf_size: size_t) -> c_int;
 }
+
+/// A negative FFmpeg return code.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AvError(pub c_int);
+
+impl AvError {
+    /// Passes non-negative return values through and wraps negative ones.
+    #[inline]
+    pub fn check(ret: c_int) -> Result<c_int, AvError> {
+        if ret < 0 {
+            Err(AvError(ret))
+        } else {
+            Ok(ret)
+        }
+    }
+
+    #[inline]
+    pub fn code(&self) -> c_int {
+        self.0
+    }
+}
+
+impl fmt::Display for AvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buf = [0 as c_char; AV_ERROR_MAX_STRING_SIZE];
+
+        unsafe {
+            av_strerror(self.0, buf.as_mut_ptr(), buf.len());
+            f.write_str(&CStr::from_ptr(buf.as_ptr()).to_string_lossy())
+        }
+    }
+}
+
+impl error::Error for AvError {
+    fn description(&self) -> &str {
+        "FFmpeg error"
+    }
+}
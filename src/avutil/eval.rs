@@ -0,0 +1,381 @@
+use std::error;
+use std::ffi::CString;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use libc::{c_char, c_void, EINVAL};
+use {av_expr_eval, av_expr_free, av_expr_parse, av_expr_parse_and_eval, AVExpr, AvError,
+     AVERROR};
+
+type UnaryFn = Box<dyn Fn(f64) -> f64 + Send>;
+type BinaryFn = Box<dyn Fn(f64, f64) -> f64 + Send>;
+type UnaryCallback = Option<unsafe extern "C" fn(*mut c_void, f64) -> f64>;
+type BinaryCallback = Option<unsafe extern "C" fn(*mut c_void, f64, f64) -> f64>;
+
+/// Maximum number of unary, and of binary, functions per expression.
+pub const EXPR_MAX_FUNCTIONS: usize = 16;
+
+const BUILTIN_CONSTANTS: &[&str] = &["E", "PI", "PHI", "QP2LAMBDA"];
+const BUILTIN_FUNCTIONS: &[&str] = &[
+    "abs", "acos", "asin", "atan", "atan2", "between", "bitand", "bitor", "ceil", "clip", "cos",
+    "cosh", "eq", "exp", "floor", "gauss", "gcd", "gt", "gte", "hypot", "if", "ifnot", "isinf",
+    "isnan", "ld", "lerp", "log", "lt", "lte", "max", "min", "mod", "not", "pow", "print",
+    "random", "root", "round", "sgn", "sin", "sinh", "sqrt", "squish", "st", "tan", "tanh",
+    "taylor", "time", "trunc", "while",
+];
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ExprErrorKind {
+    /// A constant or function that is neither built in nor registered.
+    UndefinedName(String),
+    UnbalancedParenthesis,
+    /// Any other syntax error, which FFmpeg only reports through `av_log`.
+    Syntax,
+    /// A name containing a nul byte, or too many functions.
+    InvalidArgument,
+}
+
+/// Why an expression failed to parse, with the byte offset of the
+/// offending token when it could be located.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExprError {
+    pub kind: ExprErrorKind,
+    pub position: Option<usize>,
+}
+
+impl ExprError {
+    fn invalid() -> Self {
+        ExprError {
+            kind: ExprErrorKind::InvalidArgument,
+            position: None,
+        }
+    }
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ExprErrorKind::UndefinedName(ref name) => {
+                write!(f, "undefined constant or function '{}'", name)?
+            }
+            ExprErrorKind::UnbalancedParenthesis => f.write_str("unbalanced parenthesis")?,
+            ExprErrorKind::Syntax => f.write_str("invalid expression")?,
+            ExprErrorKind::InvalidArgument => f.write_str("invalid constant or function name")?,
+        }
+
+        match self.position {
+            Some(position) => write!(f, " at position {}", position),
+            None => Ok(()),
+        }
+    }
+}
+
+impl error::Error for ExprError {
+    fn description(&self) -> &str {
+        "invalid expression"
+    }
+}
+
+struct Functions {
+    unary: Vec<UnaryFn>,
+    binary: Vec<BinaryFn>,
+}
+
+// `av_expr_eval` hands every function the same opaque pointer, so each
+// registered closure gets its own trampoline knowing its index.
+macro_rules! trampolines {
+    ($($index:expr),*) => {
+        static UNARY: [UnaryCallback; EXPR_MAX_FUNCTIONS] = [$(
+            Some({
+                unsafe extern "C" fn call(opaque: *mut c_void, x: f64) -> f64 {
+                    let functions = &*(opaque as *const Functions);
+                    guard(|| (functions.unary[$index])(x))
+                }
+                call
+            }),
+        )*];
+
+        static BINARY: [BinaryCallback; EXPR_MAX_FUNCTIONS] = [$(
+            Some({
+                unsafe extern "C" fn call(opaque: *mut c_void, x: f64, y: f64) -> f64 {
+                    let functions = &*(opaque as *const Functions);
+                    guard(|| (functions.binary[$index])(x, y))
+                }
+                call
+            }),
+        )*];
+    };
+}
+
+trampolines!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+
+/// Panics must not unwind into C, they evaluate to NaN instead.
+fn guard<F: FnOnce() -> f64>(f: F) -> f64 {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(::std::f64::NAN)
+}
+
+/// A parsed FFmpeg expression, e.g. `"sin(t)*w/2"`, as used in filter
+/// options.
+pub struct Expr {
+    ptr: *mut AVExpr,
+    constants: usize,
+    functions: Box<Functions>,
+}
+
+unsafe impl Send for Expr {}
+
+impl Expr {
+    /// Parses an expression over the given constants, without functions
+    /// besides FFmpeg's built in ones.
+    pub fn parse(expr: &str, constants: &[&str]) -> Result<Expr, ExprError> {
+        constants
+            .iter()
+            .fold(ExprBuilder::new(), |builder, name| builder.constant(name))
+            .parse(expr)
+    }
+
+    /// Parses and evaluates an expression in one step.
+    pub fn parse_and_eval(expr: &str, constants: &[(&str, f64)]) -> Result<f64, ExprError> {
+        let c_expr = CString::new(expr).map_err(|_| ExprError::invalid())?;
+        let names = constants
+            .iter()
+            .map(|&(name, _)| CString::new(name))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ExprError::invalid())?;
+        let name_ptrs = null_terminated(&names);
+        let values = constants.iter().map(|&(_, value)| value).collect::<Vec<_>>();
+        let mut result = 0.0;
+
+        let ret = unsafe {
+            av_expr_parse_and_eval(
+                &mut result,
+                c_expr.as_ptr(),
+                name_ptrs.as_ptr(),
+                values.as_ptr(),
+                ptr::null(),
+                ptr::null(),
+                ptr::null(),
+                ptr::null(),
+                ptr::null_mut(),
+                0,
+                ptr::null_mut(),
+            )
+        };
+
+        if ret < 0 {
+            let names = constants.iter().map(|&(name, _)| name).collect::<Vec<_>>();
+            Err(locate_error(expr, &names, &[], &[]))
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Evaluates the expression with `values` for the constants, in the
+    /// order they were declared.
+    ///
+    /// Takes `&mut self` as expressions may store into their variables.
+    /// Fails with `EINVAL` when `values` doesn't hold one value per
+    /// constant.
+    pub fn eval(&mut self, values: &[f64]) -> Result<f64, AvError> {
+        if values.len() != self.constants {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        unsafe {
+            let opaque = &*self.functions as *const Functions as *mut c_void;
+            Ok(av_expr_eval(self.ptr, values.as_ptr(), opaque))
+        }
+    }
+}
+
+impl Drop for Expr {
+    fn drop(&mut self) {
+        unsafe { av_expr_free(self.ptr) }
+    }
+}
+
+/// Declares the constants and registers the functions an expression can
+/// refer to.
+#[derive(Default)]
+pub struct ExprBuilder {
+    constants: Vec<String>,
+    unary: Vec<(String, UnaryFn)>,
+    binary: Vec<(String, BinaryFn)>,
+}
+
+impl ExprBuilder {
+    pub fn new() -> Self {
+        ExprBuilder::default()
+    }
+
+    pub fn constant(mut self, name: &str) -> Self {
+        self.constants.push(name.to_owned());
+        self
+    }
+
+    pub fn unary<F>(mut self, name: &str, function: F) -> Self
+    where
+        F: Fn(f64) -> f64 + Send + 'static,
+    {
+        self.unary.push((name.to_owned(), Box::new(function)));
+        self
+    }
+
+    pub fn binary<F>(mut self, name: &str, function: F) -> Self
+    where
+        F: Fn(f64, f64) -> f64 + Send + 'static,
+    {
+        self.binary.push((name.to_owned(), Box::new(function)));
+        self
+    }
+
+    pub fn parse(self, expr: &str) -> Result<Expr, ExprError> {
+        if self.unary.len() > EXPR_MAX_FUNCTIONS || self.binary.len() > EXPR_MAX_FUNCTIONS {
+            return Err(ExprError::invalid());
+        }
+
+        let c_expr = CString::new(expr).map_err(|_| ExprError::invalid())?;
+        let constants = c_strings(self.constants.iter())?;
+        let unary = c_strings(self.unary.iter().map(|function| &function.0))?;
+        let binary = c_strings(self.binary.iter().map(|function| &function.0))?;
+        let (constant_ptrs, unary_ptrs, binary_ptrs) = (
+            null_terminated(&constants),
+            null_terminated(&unary),
+            null_terminated(&binary),
+        );
+        let mut ptr = ptr::null_mut();
+
+        let ret = unsafe {
+            av_expr_parse(
+                &mut ptr,
+                c_expr.as_ptr(),
+                constant_ptrs.as_ptr(),
+                unary_ptrs.as_ptr(),
+                UNARY.as_ptr(),
+                binary_ptrs.as_ptr(),
+                BINARY.as_ptr(),
+                0,
+                ptr::null_mut(),
+            )
+        };
+
+        if ret < 0 {
+            let constants = self.constants.iter().map(|name| &name[..]).collect::<Vec<_>>();
+            let unary = self.unary.iter().map(|function| &function.0[..]).collect::<Vec<_>>();
+            let binary = self.binary.iter().map(|function| &function.0[..]).collect::<Vec<_>>();
+
+            return Err(locate_error(expr, &constants, &unary, &binary));
+        }
+
+        Ok(Expr {
+            ptr,
+            constants: self.constants.len(),
+            functions: Box::new(Functions {
+                unary: self.unary.into_iter().map(|(_, f)| f).collect(),
+                binary: self.binary.into_iter().map(|(_, f)| f).collect(),
+            }),
+        })
+    }
+}
+
+fn c_strings<'a, I>(names: I) -> Result<Vec<CString>, ExprError>
+where
+    I: Iterator<Item = &'a String>,
+{
+    names
+        .map(|name| CString::new(&name[..]).map_err(|_| ExprError::invalid()))
+        .collect()
+}
+
+fn null_terminated(strings: &[CString]) -> Vec<*const c_char> {
+    strings
+        .iter()
+        .map(|s| s.as_ptr())
+        .chain(Some(ptr::null()))
+        .collect()
+}
+
+/// Finds the first undefined name or unbalanced parenthesis, as FFmpeg
+/// reports parse errors only through `av_log`.
+fn locate_error(expr: &str, constants: &[&str], unary: &[&str], binary: &[&str]) -> ExprError {
+    let bytes = expr.as_bytes();
+    let mut open = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if c.is_ascii_digit() || c == b'.' {
+            // Numbers may carry exponents, hex digits and SI suffixes
+            i += 1;
+            while i < bytes.len() {
+                let exponent = (bytes[i] == b'+' || bytes[i] == b'-')
+                    && (bytes[i - 1] == b'e' || bytes[i - 1] == b'E');
+                if !(bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.' || exponent) {
+                    break;
+                }
+                i += 1;
+            }
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            let start = i;
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+
+            let name = &expr[start..i];
+            let call = bytes[i..].iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'(');
+            let known = if call {
+                BUILTIN_FUNCTIONS.contains(&name) || unary.contains(&name) || binary.contains(&name)
+            } else {
+                BUILTIN_CONSTANTS.contains(&name) || constants.contains(&name)
+            };
+
+            if !known {
+                return ExprError {
+                    kind: ExprErrorKind::UndefinedName(name.to_owned()),
+                    position: Some(start),
+                };
+            }
+        } else {
+            if c == b'(' {
+                open.push(i);
+            } else if c == b')' && open.pop().is_none() {
+                return ExprError {
+                    kind: ExprErrorKind::UnbalancedParenthesis,
+                    position: Some(i),
+                };
+            }
+            i += 1;
+        }
+    }
+
+    match open.pop() {
+        Some(position) => ExprError {
+            kind: ExprErrorKind::UnbalancedParenthesis,
+            position: Some(position),
+        },
+        None => ExprError {
+            kind: ExprErrorKind::Syntax,
+            position: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eval_checks_constant_count() {
+        let mut expr = ExprBuilder::new()
+            .constant("w")
+            .unary("half", |x| x / 2.0)
+            .parse("half(w)+1")
+            .unwrap();
+
+        assert_eq!(expr.eval(&[6.0]), Ok(4.0));
+        assert_eq!(expr.eval(&[]), Err(AvError(AVERROR(EINVAL))));
+        assert_eq!(expr.eval(&[6.0, 1.0]), Err(AvError(AVERROR(EINVAL))));
+    }
+}
@@ -0,0 +1,355 @@
+use std::cmp;
+use std::marker::PhantomData;
+use std::mem;
+use std::slice;
+
+use libc::{c_int, c_uint, c_void, EINVAL, ENOMEM};
+use {av_fifo_alloc, av_fifo_drain, av_fifo_free, av_fifo_generic_peek_at, av_fifo_generic_read,
+     av_fifo_generic_write, av_fifo_grow, av_fifo_realloc2, av_fifo_reset, av_fifo_size,
+     av_fifo_space, AVFifoBuffer, AvError, AVERROR};
+
+/// A ring buffer of bytes, growing as needed on writes.
+pub struct Fifo {
+    ptr: *mut AVFifoBuffer,
+}
+
+unsafe impl Send for Fifo {}
+
+impl Fifo {
+    /// A FIFO with room for `capacity` bytes before it needs to grow.
+    pub fn new(capacity: usize) -> Result<Self, AvError> {
+        let capacity = to_c_int(capacity)?;
+        let ptr = unsafe { av_fifo_alloc(capacity as c_uint) };
+
+        if ptr.is_null() {
+            Err(AvError(AVERROR(ENOMEM)))
+        } else {
+            Ok(Fifo { ptr })
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const AVFifoBuffer {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVFifoBuffer {
+        self.ptr
+    }
+
+    /// Number of bytes available for reading.
+    pub fn size(&self) -> usize {
+        unsafe { av_fifo_size(self.ptr) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size() == 0
+    }
+
+    /// Number of bytes that can be written without growing.
+    pub fn space(&self) -> usize {
+        unsafe { av_fifo_space(self.ptr) as usize }
+    }
+
+    /// Makes room for `additional` bytes on top of those queued, i.e.
+    /// until `space() >= additional`, reallocating the buffer if needed.
+    pub fn grow(&mut self, additional: usize) -> Result<(), AvError> {
+        // FFmpeg adds it to the queued size as an unsigned int
+        to_c_int(self.size().saturating_add(additional))?;
+        let additional = to_c_int(additional)?;
+        unsafe { AvError::check(av_fifo_grow(self.ptr, additional as c_uint)).map(|_| ()) }
+    }
+
+    /// Grows the buffer to `capacity` bytes in total, never shrinking it.
+    pub fn realloc(&mut self, capacity: usize) -> Result<(), AvError> {
+        let capacity = to_c_int(capacity)?;
+        unsafe { AvError::check(av_fifo_realloc2(self.ptr, capacity as c_uint)).map(|_| ()) }
+    }
+
+    /// Appends all of `data`, growing the buffer if needed.
+    pub fn write(&mut self, data: &[u8]) -> Result<(), AvError> {
+        let len = to_c_int(data.len())?;
+        if data.len() > self.space() {
+            self.grow(data.len())?;
+        }
+
+        // `av_fifo_generic_write` would wrap over unread data
+        if data.len() > self.space() {
+            return Err(AvError(AVERROR(ENOMEM)));
+        }
+
+        unsafe {
+            av_fifo_generic_write(self.ptr, data.as_ptr() as *mut c_void, len, None);
+        }
+
+        Ok(())
+    }
+
+    /// Moves up to `buf.len()` bytes into `buf`, returning the number read.
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        unsafe { self.read_raw(buf.as_mut_ptr() as *mut c_void, buf.len()) }
+    }
+
+    /// Copies up to `buf.len()` bytes starting `offset` bytes from the front
+    /// into `buf`, without removing them, returning the number copied.
+    pub fn peek_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        unsafe { self.peek_raw(offset, buf.as_mut_ptr() as *mut c_void, buf.len()) }
+    }
+
+    /// Like `peek_at` from the front.
+    pub fn peek(&self, buf: &mut [u8]) -> usize {
+        self.peek_at(0, buf)
+    }
+
+    /// Discards up to `len` bytes from the front.
+    pub fn drain(&mut self, len: usize) {
+        let len = cmp::min(len, self.size());
+        unsafe { av_fifo_drain(self.ptr, len as c_int) }
+    }
+
+    /// Discards every byte.
+    pub fn reset(&mut self) {
+        unsafe { av_fifo_reset(self.ptr) }
+    }
+
+    // `dst` may be uninitialized memory, which must not be viewed as a slice
+    unsafe fn read_raw(&mut self, dst: *mut c_void, len: usize) -> usize {
+        let len = cmp::min(len, self.size());
+        if len > 0 {
+            av_fifo_generic_read(self.ptr, dst, len as c_int, None);
+        }
+
+        len
+    }
+
+    unsafe fn peek_raw(&self, offset: usize, dst: *mut c_void, len: usize) -> usize {
+        let len = cmp::min(len, self.size().saturating_sub(offset));
+        if len > 0 {
+            av_fifo_generic_peek_at(self.ptr, dst, offset as c_int, len as c_int, None);
+        }
+
+        len
+    }
+}
+
+impl Drop for Fifo {
+    fn drop(&mut self) {
+        unsafe { av_fifo_free(self.ptr) }
+    }
+}
+
+/// A type that can be copied byte for byte into a `TypedFifo`.
+///
+/// Implementors must not contain padding bytes, as those are uninitialized
+/// and would be read as part of the value, nor pointers to data the FIFO
+/// would then outlive. `#[repr(C)]` structs whose fields are `Plain` and
+/// leave no gaps qualify.
+pub unsafe trait Plain: Copy {}
+
+unsafe impl Plain for u8 {}
+unsafe impl Plain for u16 {}
+unsafe impl Plain for u32 {}
+unsafe impl Plain for u64 {}
+unsafe impl Plain for usize {}
+unsafe impl Plain for i8 {}
+unsafe impl Plain for i16 {}
+unsafe impl Plain for i32 {}
+unsafe impl Plain for i64 {}
+unsafe impl Plain for isize {}
+unsafe impl Plain for f32 {}
+unsafe impl Plain for f64 {}
+
+/// A `Fifo` of fixed-size values, copied in and out byte for byte.
+pub struct TypedFifo<T: Plain> {
+    fifo: Fifo,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Plain + Send> Send for TypedFifo<T> {}
+
+impl<T: Plain> TypedFifo<T> {
+    /// A FIFO with room for `capacity` values before it needs to grow.
+    ///
+    /// Zero-sized types are not supported.
+    pub fn new(capacity: usize) -> Result<Self, AvError> {
+        if mem::size_of::<T>() == 0 {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        Ok(TypedFifo {
+            fifo: Fifo::new(bytes::<T>(capacity)?)?,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The underlying byte FIFO.
+    pub fn as_fifo(&self) -> &Fifo {
+        &self.fifo
+    }
+
+    /// Number of values available for reading.
+    pub fn len(&self) -> usize {
+        self.fifo.size() / mem::size_of::<T>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fifo.is_empty()
+    }
+
+    /// Number of values that can be written without growing.
+    pub fn space(&self) -> usize {
+        self.fifo.space() / mem::size_of::<T>()
+    }
+
+    /// Makes room for `additional` values on top of those queued.
+    pub fn grow(&mut self, additional: usize) -> Result<(), AvError> {
+        self.fifo.grow(bytes::<T>(additional)?)
+    }
+
+    /// Grows the buffer to hold `capacity` values in total.
+    pub fn realloc(&mut self, capacity: usize) -> Result<(), AvError> {
+        self.fifo.realloc(bytes::<T>(capacity)?)
+    }
+
+    pub fn push(&mut self, value: T) -> Result<(), AvError> {
+        self.write(slice::from_ref(&value))
+    }
+
+    /// Appends all of `values`, growing the buffer if needed.
+    pub fn write(&mut self, values: &[T]) -> Result<(), AvError> {
+        // `Plain` values have no padding, so every byte is initialized
+        let data = unsafe {
+            slice::from_raw_parts(values.as_ptr() as *const u8, bytes::<T>(values.len())?)
+        };
+
+        self.fifo.write(data)
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let mut value = mem::MaybeUninit::<T>::uninit();
+        let size = mem::size_of::<T>();
+
+        unsafe {
+            if self.fifo.read_raw(value.as_mut_ptr() as *mut c_void, size) == size {
+                Some(value.assume_init())
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Moves up to `buf.len()` values into `buf`, returning the number read.
+    pub fn read(&mut self, buf: &mut [T]) -> usize {
+        let len = cmp::min(buf.len(), self.len());
+        let size = len * mem::size_of::<T>();
+
+        unsafe { self.fifo.read_raw(buf.as_mut_ptr() as *mut c_void, size) / mem::size_of::<T>() }
+    }
+
+    /// The value `index` values from the front, without removing it.
+    pub fn peek_at(&self, index: usize) -> Option<T> {
+        let mut value = mem::MaybeUninit::<T>::uninit();
+        let size = mem::size_of::<T>();
+        let offset = index.saturating_mul(size);
+
+        unsafe {
+            if self.fifo.peek_raw(offset, value.as_mut_ptr() as *mut c_void, size) == size {
+                Some(value.assume_init())
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Discards up to `len` values from the front.
+    pub fn drain(&mut self, len: usize) {
+        let len = cmp::min(len, self.len());
+        self.fifo.drain(len * mem::size_of::<T>())
+    }
+
+    /// Discards every value.
+    pub fn reset(&mut self) {
+        self.fifo.reset()
+    }
+}
+
+/// Size in bytes of `len` values, as FIFO sizes are `int`s.
+fn bytes<T>(len: usize) -> Result<usize, AvError> {
+    len.checked_mul(mem::size_of::<T>())
+        .ok_or_else(|| AvError(AVERROR(EINVAL)))
+        .and_then(to_c_int)
+        .map(|len| len as usize)
+}
+
+fn to_c_int(len: usize) -> Result<c_int, AvError> {
+    if len > c_int::max_value() as usize {
+        Err(AvError(AVERROR(EINVAL)))
+    } else {
+        Ok(len as c_int)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_values_wrap_around() {
+        let mut fifo = TypedFifo::<u32>::new(4).unwrap();
+
+        fifo.write(&[1, 2, 3]).unwrap();
+        assert_eq!(fifo.pop(), Some(1));
+        assert_eq!(fifo.pop(), Some(2));
+
+        // Wraps around the end of the buffer without growing it
+        fifo.write(&[4, 5]).unwrap();
+        assert_eq!(fifo.space(), 1);
+
+        // Needs more than the free space, so the buffer grows around the
+        // queued values rather than overwriting them
+        fifo.write(&[6, 7, 8, 9]).unwrap();
+        assert_eq!(fifo.len(), 7);
+        assert!(fifo.space() < 4);
+        assert_eq!(fifo.peek_at(1), Some(4));
+        assert_eq!(fifo.peek_at(7), None);
+
+        let mut buf = [0; 8];
+        assert_eq!(fifo.read(&mut buf), 7);
+        assert_eq!(buf[..7], [3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(fifo.pop(), None);
+    }
+
+    #[test]
+    fn grow_counts_queued_bytes() {
+        let mut fifo = Fifo::new(16).unwrap();
+        fifo.write(&[1; 12]).unwrap();
+
+        fifo.grow(20).unwrap();
+        assert!(fifo.space() >= 20);
+
+        fifo.write(&[2; 20]).unwrap();
+        assert_eq!(fifo.size(), 32);
+
+        let mut buf = [0; 32];
+        assert_eq!(fifo.read(&mut buf), 32);
+        assert_eq!(buf[..12], [1; 12]);
+        assert_eq!(buf[12..], [2; 20]);
+    }
+
+    #[test]
+    fn bytes_peek_and_drain() {
+        let mut fifo = Fifo::new(2).unwrap();
+        fifo.write(b"hello").unwrap();
+
+        let mut buf = [0; 3];
+        assert_eq!(fifo.peek_at(3, &mut buf), 2);
+        assert_eq!(&buf[..2], b"lo");
+
+        fifo.drain(1);
+        assert_eq!(fifo.read(&mut buf), 3);
+        assert_eq!(&buf, b"ell");
+        assert_eq!(fifo.size(), 1);
+
+        fifo.reset();
+        assert!(fifo.is_empty());
+    }
+}
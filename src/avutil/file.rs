@@ -0,0 +1,87 @@
+use std::ops::Deref;
+use std::path::Path;
+use std::{ptr, slice};
+
+use libc::{c_void, EINVAL};
+use {av_file_map, av_file_unmap, cstring, AvError, AVERROR};
+
+/// The contents of a file, mapped into memory where supported or read into
+/// a buffer otherwise.
+///
+/// The data is not followed by padding, so it has to be copied, e.g. into
+/// an `AvVec`, before being handed to parsers or `av_probe_input_format`.
+pub struct MappedFile {
+    data: *mut u8,
+    len: usize,
+}
+
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+impl MappedFile {
+    /// Maps the file read-only. Failures are also logged under the `FILE`
+    /// context.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, AvError> {
+        unsafe { MappedFile::open_with_log_context(path, ptr::null_mut()) }
+    }
+
+    /// Like `open`, logging failures as a child of `log_ctx`, a pointer to a
+    /// struct whose first member is a `const AVClass *`, or null.
+    pub unsafe fn open_with_log_context<P: AsRef<Path>>(
+        path: P,
+        log_ctx: *mut c_void,
+    ) -> Result<Self, AvError> {
+        let path = path.as_ref()
+            .to_str()
+            .ok_or_else(|| AvError(AVERROR(EINVAL)))?;
+        let path = cstring(path)?;
+        let mut data = ptr::null_mut();
+        let mut len = 0;
+
+        AvError::check(av_file_map(path.as_ptr(), &mut data, &mut len, 0, log_ctx))?;
+
+        Ok(MappedFile {
+            data,
+            len: len as usize,
+        })
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // Empty files are not mapped at all
+        if self.data.is_null() {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(self.data, self.len) }
+        }
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        if !self.data.is_null() {
+            unsafe { av_file_unmap(self.data, self.len as _) }
+        }
+    }
+}
@@ -0,0 +1,547 @@
+use std::ops::{Deref, DerefMut};
+use std::{mem, ptr, slice};
+
+use libc::c_int;
+use {av_frame_alloc, av_frame_clone, av_frame_copy_props, av_frame_free, av_frame_get_buffer,
+     av_frame_is_writable, av_frame_make_writable, av_frame_ref, av_frame_unref,
+     av_pix_fmt_count_planes, av_pix_fmt_desc_get, AVChromaLocation, AVColorPrimaries,
+     AVColorRange, AVColorSpace, AVColorTransferCharacteristic, AVFrame, AVPictureType,
+     AVPixelFormat, AVSampleFormat, AvError, ChannelLayout, DictionaryRef, SampleFormat,
+     AV_NOPTS_VALUE, AV_PIX_FMT_FLAG_HWACCEL, AV_PIX_FMT_FLAG_PAL, AV_PIX_FMT_FLAG_PSEUDOPAL};
+
+/// An owned `AVFrame`, whose data is held through reference counted buffers.
+///
+/// Cloning adds a reference to the same data rather than copying it.
+pub struct Frame {
+    ptr: *mut AVFrame,
+}
+
+unsafe impl Send for Frame {}
+
+impl Frame {
+    /// An empty frame, without any data attached.
+    pub fn new() -> Self {
+        let ptr = unsafe { av_frame_alloc() };
+        assert!(!ptr.is_null(), "av_frame_alloc: out of memory");
+
+        Frame { ptr }
+    }
+
+    /// Takes ownership of a frame allocated with `av_frame_alloc`.
+    pub unsafe fn from_raw(ptr: *mut AVFrame) -> Self {
+        Frame { ptr }
+    }
+
+    pub fn into_raw(self) -> *mut AVFrame {
+        let ptr = self.ptr;
+        ::std::mem::forget(self);
+
+        ptr
+    }
+
+    pub fn as_ptr(&self) -> *const AVFrame {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVFrame {
+        self.ptr
+    }
+
+    /// Whether no data is attached to the frame.
+    pub fn is_empty(&self) -> bool {
+        unsafe { (*self.ptr).buf[0].is_null() && (*self.ptr).data[0].is_null() }
+    }
+
+    /// Allocates zeroed buffers for the format, dimensions or sample count
+    /// and channel layout already set on the frame, with line sizes aligned
+    /// to `align` (0 for the default).
+    pub fn get_buffer(&mut self, align: usize) -> Result<(), AvError> {
+        unsafe {
+            AvError::check(av_frame_get_buffer(self.ptr, align as c_int))?;
+
+            // The planes can be read through safe code before being written
+            let frame = &*self.ptr;
+            let extended = if frame.extended_buf.is_null() {
+                &[][..]
+            } else {
+                slice::from_raw_parts(frame.extended_buf, frame.nb_extended_buf as usize)
+            };
+
+            for &buf in frame.buf.iter().chain(extended) {
+                if !buf.is_null() {
+                    ptr::write_bytes((*buf).data, 0, (*buf).size as usize);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the data is only referenced by this frame.
+    pub fn is_writable(&self) -> bool {
+        unsafe { av_frame_is_writable(self.ptr) != 0 }
+    }
+
+    /// Copies the data into new buffers, unless it is already writable.
+    pub fn make_writable(&mut self) -> Result<(), AvError> {
+        unsafe { AvError::check(av_frame_make_writable(self.ptr)).map(|_| ()) }
+    }
+
+    /// Replaces the frame with a new reference to the data and properties of
+    /// `src`.
+    pub fn ref_from(&mut self, src: &Frame) -> Result<(), AvError> {
+        unsafe {
+            av_frame_unref(self.ptr);
+            AvError::check(av_frame_ref(self.ptr, src.ptr)).map(|_| ())
+        }
+    }
+
+    /// Drops the data and resets every field to its default.
+    pub fn unref(&mut self) {
+        unsafe { av_frame_unref(self.ptr) }
+    }
+
+    /// Copies the metadata, i.e. everything but the data and its layout,
+    /// from `src`.
+    pub fn copy_props(&mut self, src: &Frame) -> Result<(), AvError> {
+        unsafe { AvError::check(av_frame_copy_props(self.ptr, src.ptr)).map(|_| ()) }
+    }
+
+    /// The presentation timestamp, in the time base of the stream.
+    pub fn pts(&self) -> Option<i64> {
+        unsafe { timestamp((*self.ptr).pts) }
+    }
+
+    pub fn set_pts(&mut self, pts: Option<i64>) {
+        unsafe { (*self.ptr).pts = pts.unwrap_or(AV_NOPTS_VALUE) }
+    }
+
+    /// The timestamp guessed by the decoder from the pts and dts.
+    pub fn best_effort_timestamp(&self) -> Option<i64> {
+        unsafe { timestamp((*self.ptr).best_effort_timestamp) }
+    }
+
+    pub fn is_key_frame(&self) -> bool {
+        unsafe { (*self.ptr).key_frame != 0 }
+    }
+
+    pub fn set_key_frame(&mut self, value: bool) {
+        unsafe { (*self.ptr).key_frame = value as c_int }
+    }
+
+    pub fn picture_type(&self) -> AVPictureType {
+        unsafe { (*self.ptr).pict_type }
+    }
+
+    pub fn set_picture_type(&mut self, value: AVPictureType) {
+        unsafe { (*self.ptr).pict_type = value }
+    }
+
+    pub fn color_range(&self) -> AVColorRange {
+        unsafe { (*self.ptr).color_range }
+    }
+
+    pub fn set_color_range(&mut self, value: AVColorRange) {
+        unsafe { (*self.ptr).color_range = value }
+    }
+
+    pub fn color_primaries(&self) -> AVColorPrimaries {
+        unsafe { (*self.ptr).color_primaries }
+    }
+
+    pub fn set_color_primaries(&mut self, value: AVColorPrimaries) {
+        unsafe { (*self.ptr).color_primaries = value }
+    }
+
+    pub fn color_transfer_characteristic(&self) -> AVColorTransferCharacteristic {
+        unsafe { (*self.ptr).color_trc }
+    }
+
+    pub fn set_color_transfer_characteristic(&mut self, value: AVColorTransferCharacteristic) {
+        unsafe { (*self.ptr).color_trc = value }
+    }
+
+    pub fn color_space(&self) -> AVColorSpace {
+        unsafe { (*self.ptr).colorspace }
+    }
+
+    pub fn set_color_space(&mut self, value: AVColorSpace) {
+        unsafe { (*self.ptr).colorspace = value }
+    }
+
+    pub fn chroma_location(&self) -> AVChromaLocation {
+        unsafe { (*self.ptr).chroma_location }
+    }
+
+    pub fn set_chroma_location(&mut self, value: AVChromaLocation) {
+        unsafe { (*self.ptr).chroma_location = value }
+    }
+
+    pub fn metadata(&self) -> DictionaryRef<'_> {
+        unsafe { DictionaryRef::wrap((*self.ptr).metadata) }
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Frame::new()
+    }
+}
+
+impl Clone for Frame {
+    fn clone(&self) -> Self {
+        if self.is_empty() {
+            let mut frame = Frame::new();
+            frame.copy_props(self).expect("av_frame_copy_props");
+
+            return frame;
+        }
+
+        let ptr = unsafe { av_frame_clone(self.ptr) };
+        assert!(!ptr.is_null(), "av_frame_clone: out of memory");
+
+        Frame { ptr }
+    }
+}
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        unsafe {
+            av_frame_free(&mut self.ptr);
+        }
+    }
+}
+
+pub(crate) fn timestamp(value: i64) -> Option<i64> {
+    if value == AV_NOPTS_VALUE {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+// `AVFrame.format` holds either enum, or anything a caller stored there
+fn pixel_format(format: c_int) -> Option<AVPixelFormat> {
+    if format >= 0 && format < AVPixelFormat::AV_PIX_FMT_NB as c_int {
+        Some(unsafe { mem::transmute::<c_int, AVPixelFormat>(format) })
+    } else {
+        None
+    }
+}
+
+fn sample_format(format: c_int) -> Option<SampleFormat> {
+    if format >= 0 && format < AVSampleFormat::AV_SAMPLE_FMT_NB as c_int {
+        Some(SampleFormat(unsafe { mem::transmute::<c_int, AVSampleFormat>(format) }))
+    } else {
+        None
+    }
+}
+
+/// A frame holding an image, with one slice per plane of its pixel format.
+#[derive(Clone, Default)]
+pub struct VideoFrame(Frame);
+
+impl VideoFrame {
+    /// A frame with newly allocated, zeroed planes.
+    pub fn new(format: AVPixelFormat, width: u32, height: u32) -> Result<Self, AvError> {
+        let mut frame = Frame::new();
+
+        unsafe {
+            (*frame.ptr).format = format as c_int;
+            (*frame.ptr).width = width as c_int;
+            (*frame.ptr).height = height as c_int;
+        }
+        frame.get_buffer(0)?;
+
+        Ok(VideoFrame(frame))
+    }
+
+    pub fn into_inner(self) -> Frame {
+        self.0
+    }
+
+    /// `None` if unset or not a pixel format.
+    pub fn format(&self) -> Option<AVPixelFormat> {
+        pixel_format(unsafe { (*self.ptr).format })
+    }
+
+    pub fn width(&self) -> u32 {
+        unsafe { (*self.ptr).width as u32 }
+    }
+
+    pub fn height(&self) -> u32 {
+        unsafe { (*self.ptr).height as u32 }
+    }
+
+    /// Number of planes of the pixel format, not counting the palette.
+    pub fn planes(&self) -> usize {
+        let count = match self.format() {
+            Some(format) => unsafe { av_pix_fmt_count_planes(format) },
+            None => return 0,
+        };
+
+        if count < 0 {
+            0
+        } else {
+            count as usize
+        }
+    }
+
+    /// Size in bytes of a line of the plane, including padding, negative
+    /// if the plane is stored bottom-up.
+    pub fn stride(&self, index: usize) -> isize {
+        unsafe { (*self.ptr).linesize[index] as isize }
+    }
+
+    /// The plane's lines, `stride(index)` bytes each.
+    ///
+    /// `None` if the plane doesn't exist, lives in hardware memory, or is
+    /// stored bottom-up with a negative line size.
+    pub fn plane(&self, index: usize) -> Option<&[u8]> {
+        let len = self.plane_len(index)?;
+
+        unsafe { Some(slice::from_raw_parts((*self.ptr).data[index], len)) }
+    }
+
+    /// Like `plane`, but also `None` if the frame isn't writable.
+    pub fn plane_mut(&mut self, index: usize) -> Option<&mut [u8]> {
+        if !self.is_writable() {
+            return None;
+        }
+        let len = self.plane_len(index)?;
+
+        unsafe { Some(slice::from_raw_parts_mut((*self.ptr).data[index], len)) }
+    }
+
+    /// The 256 native endian ARGB entries of a paletted format.
+    pub fn palette(&self) -> Option<&[u8]> {
+        unsafe {
+            let desc = av_pix_fmt_desc_get(self.format()?);
+            let paletted = (AV_PIX_FMT_FLAG_PAL | AV_PIX_FMT_FLAG_PSEUDOPAL) as u64;
+
+            if desc.is_null() || (*desc).flags & paletted == 0 || (*self.ptr).data[1].is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts((*self.ptr).data[1], 256 * 4))
+            }
+        }
+    }
+
+    fn plane_len(&self, index: usize) -> Option<usize> {
+        unsafe {
+            if index >= self.planes() || (*self.ptr).data[index].is_null() {
+                return None;
+            }
+
+            plane_size(self.format()?, self.height(), index, (*self.ptr).linesize[index])
+        }
+    }
+}
+
+/// Size in bytes of a plane of an image in CPU memory, `None` for hardware
+/// formats and bottom-up planes with a negative line size.
+pub(crate) fn plane_size(
+    format: AVPixelFormat,
+    height: u32,
+    index: usize,
+    linesize: c_int,
+) -> Option<usize> {
+    unsafe {
+        let desc = av_pix_fmt_desc_get(format);
+
+        if desc.is_null() || (*desc).flags & AV_PIX_FMT_FLAG_HWACCEL as u64 != 0 || linesize < 0 {
+            return None;
+        }
+
+        let height = height as usize;
+        let height = if index == 1 || index == 2 {
+            let shift = (*desc).log2_chroma_h;
+            (height + (1 << shift) - 1) >> shift
+        } else {
+            height
+        };
+
+        Some(linesize as usize * height)
+    }
+}
+
+impl Deref for VideoFrame {
+    type Target = Frame;
+
+    fn deref(&self) -> &Frame {
+        &self.0
+    }
+}
+
+impl DerefMut for VideoFrame {
+    fn deref_mut(&mut self) -> &mut Frame {
+        &mut self.0
+    }
+}
+
+impl From<Frame> for VideoFrame {
+    fn from(frame: Frame) -> Self {
+        VideoFrame(frame)
+    }
+}
+
+/// A native sample type, matching the packed variant of a sample format.
+pub unsafe trait Sample: Copy {
+    const FORMAT: AVSampleFormat;
+}
+
+unsafe impl Sample for u8 {
+    const FORMAT: AVSampleFormat = AVSampleFormat::AV_SAMPLE_FMT_U8;
+}
+
+unsafe impl Sample for i16 {
+    const FORMAT: AVSampleFormat = AVSampleFormat::AV_SAMPLE_FMT_S16;
+}
+
+unsafe impl Sample for i32 {
+    const FORMAT: AVSampleFormat = AVSampleFormat::AV_SAMPLE_FMT_S32;
+}
+
+unsafe impl Sample for i64 {
+    const FORMAT: AVSampleFormat = AVSampleFormat::AV_SAMPLE_FMT_S64;
+}
+
+unsafe impl Sample for f32 {
+    const FORMAT: AVSampleFormat = AVSampleFormat::AV_SAMPLE_FMT_FLT;
+}
+
+unsafe impl Sample for f64 {
+    const FORMAT: AVSampleFormat = AVSampleFormat::AV_SAMPLE_FMT_DBL;
+}
+
+/// A frame holding audio, with one plane per channel for planar formats or
+/// a single interleaved plane for packed ones.
+#[derive(Clone, Default)]
+pub struct AudioFrame(Frame);
+
+impl AudioFrame {
+    /// A frame with newly allocated, zeroed planes.
+    pub fn new(
+        format: SampleFormat,
+        layout: ChannelLayout,
+        samples: usize,
+    ) -> Result<Self, AvError> {
+        let mut frame = Frame::new();
+
+        unsafe {
+            (*frame.ptr).format = format.0 as c_int;
+            (*frame.ptr).channel_layout = layout.bits();
+            (*frame.ptr).channels = layout.nb_channels() as c_int;
+            (*frame.ptr).nb_samples = samples as c_int;
+        }
+        frame.get_buffer(0)?;
+
+        Ok(AudioFrame(frame))
+    }
+
+    pub fn into_inner(self) -> Frame {
+        self.0
+    }
+
+    /// `None` if unset or not a sample format.
+    pub fn format(&self) -> Option<SampleFormat> {
+        sample_format(unsafe { (*self.ptr).format })
+    }
+
+    pub fn channel_layout(&self) -> ChannelLayout {
+        ChannelLayout::from_bits_truncate(unsafe { (*self.ptr).channel_layout })
+    }
+
+    pub fn channels(&self) -> usize {
+        unsafe { (*self.ptr).channels as usize }
+    }
+
+    /// Number of samples per channel.
+    pub fn samples(&self) -> usize {
+        unsafe { (*self.ptr).nb_samples as usize }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        unsafe { (*self.ptr).sample_rate as u32 }
+    }
+
+    pub fn set_sample_rate(&mut self, value: u32) {
+        unsafe { (*self.ptr).sample_rate = value as c_int }
+    }
+
+    pub fn planes(&self) -> usize {
+        match self.format() {
+            Some(format) if format.is_planar() => self.channels(),
+            Some(_) => 1,
+            None => 0,
+        }
+    }
+
+    /// The samples of a channel, or of every channel interleaved for packed
+    /// formats.
+    ///
+    /// `None` if the plane doesn't exist or `T` doesn't match the format.
+    pub fn plane<T: Sample>(&self, index: usize) -> Option<&[T]> {
+        let len = self.plane_len::<T>(index)?;
+
+        unsafe { Some(slice::from_raw_parts(self.plane_ptr(index) as *const T, len)) }
+    }
+
+    /// Like `plane`, but also `None` if the frame isn't writable.
+    pub fn plane_mut<T: Sample>(&mut self, index: usize) -> Option<&mut [T]> {
+        if !self.is_writable() {
+            return None;
+        }
+        let len = self.plane_len::<T>(index)?;
+
+        unsafe { Some(slice::from_raw_parts_mut(self.plane_ptr(index) as *mut T, len)) }
+    }
+
+    fn plane_ptr(&self, index: usize) -> *mut u8 {
+        unsafe { *(*self.ptr).extended_data.add(index) }
+    }
+
+    fn plane_len<T: Sample>(&self, index: usize) -> Option<usize> {
+        let format = self.format()?;
+
+        if format.packed().0 != T::FORMAT || index >= self.planes() {
+            return None;
+        }
+
+        let data = unsafe {
+            if (*self.ptr).extended_data.is_null() {
+                return None;
+            }
+            self.plane_ptr(index)
+        };
+        if data.is_null() || data as usize % mem::align_of::<T>() != 0 {
+            return None;
+        }
+
+        if format.is_planar() {
+            Some(self.samples())
+        } else {
+            Some(self.samples() * self.channels())
+        }
+    }
+}
+
+impl Deref for AudioFrame {
+    type Target = Frame;
+
+    fn deref(&self) -> &Frame {
+        &self.0
+    }
+}
+
+impl DerefMut for AudioFrame {
+    fn deref_mut(&mut self) -> &mut Frame {
+        &mut self.0
+    }
+}
+
+impl From<Frame> for AudioFrame {
+    fn from(frame: Frame) -> Self {
+        AudioFrame(frame)
+    }
+}
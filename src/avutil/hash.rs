@@ -0,0 +1,738 @@
+use std::{ptr, slice};
+
+use libc::{c_int, c_uint, c_ulong, c_void, EINVAL};
+use AVCRCId::*;
+use AVHMACType::*;
+use {av_adler32_update, av_crc, av_crc_get_table, av_free, av_hash_alloc, av_hash_final,
+     av_hash_freep, av_hash_get_name, av_hash_get_size, av_hash_init, av_hash_names,
+     av_hash_update, av_hmac_alloc, av_hmac_final, av_hmac_free, av_hmac_init, av_hmac_update,
+     av_md5_alloc, av_md5_final, av_md5_init, av_md5_update, av_murmur3_alloc, av_murmur3_final,
+     av_murmur3_init_seeded, av_murmur3_update, av_ripemd_alloc, av_ripemd_final, av_ripemd_init,
+     av_ripemd_update, av_sha512_alloc, av_sha512_final, av_sha512_init, av_sha512_update,
+     av_sha_alloc, av_sha_final, av_sha_init, av_sha_update, cstring, ptr_to_str, AVCRCId,
+     AVHMACType, AVHashContext, AVCRC, AVHMAC, AVMD5, AVMurMur3, AVRIPEMD, AVSHA, AVSHA512,
+     AvError, AVERROR};
+
+/// A streaming hash or checksum.
+pub trait Digest {
+    fn update(&mut self, data: &[u8]);
+
+    /// Size in bytes of the output.
+    fn output_size(&self) -> usize;
+
+    /// Writes the output to the first `output_size()` bytes of `out` and
+    /// resets the state.
+    fn finalize_into(&mut self, out: &mut [u8]);
+
+    /// Discards everything hashed so far.
+    fn reset(&mut self);
+
+    fn finalize(&mut self) -> Vec<u8> {
+        let mut out = vec![0; self.output_size()];
+        self.finalize_into(&mut out);
+
+        out
+    }
+}
+
+// Older FFmpeg versions take `int` lengths
+fn chunks(data: &[u8]) -> slice::Chunks<'_, u8> {
+    data.chunks(c_int::max_value() as usize)
+}
+
+fn check_output(out: &[u8], size: usize) {
+    assert!(out.len() >= size, "digest output needs {} bytes, got {}", size, out.len());
+}
+
+/// Adler-32 checksum, as used by zlib.
+#[derive(Copy, Clone, Debug)]
+pub struct Adler32 {
+    value: c_ulong,
+}
+
+impl Adler32 {
+    pub fn new() -> Self {
+        Adler32 { value: 1 }
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value as u32
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Adler32::new()
+    }
+}
+
+impl Digest for Adler32 {
+    fn update(&mut self, data: &[u8]) {
+        for chunk in chunks(data) {
+            self.value = unsafe { av_adler32_update(self.value, chunk.as_ptr(), chunk.len() as _) };
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        4
+    }
+
+    fn finalize_into(&mut self, out: &mut [u8]) {
+        check_output(out, 4);
+        out[..4].copy_from_slice(&be_bytes(self.value(), 4));
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.value = 1;
+    }
+}
+
+/// A cyclic redundancy check over one of FFmpeg's predefined polynomials.
+#[derive(Copy, Clone, Debug)]
+pub struct Crc {
+    table: *const AVCRC,
+    id: AVCRCId,
+    init: u32,
+    xor_out: u32,
+    value: u32,
+}
+
+unsafe impl Send for Crc {}
+unsafe impl Sync for Crc {}
+
+impl Crc {
+    /// A CRC starting from `init`, with `xor_out` applied to the output.
+    pub fn new(id: AVCRCId, init: u32, xor_out: u32) -> Result<Self, AvError> {
+        // `av_crc_get_table` asserts on an id without a table, rather than
+        // returning null as documented
+        if id == AV_CRC_MAX {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let table = unsafe { av_crc_get_table(id) };
+
+        if table.is_null() {
+            Err(AvError(AVERROR(EINVAL)))
+        } else {
+            Ok(Crc {
+                table,
+                id,
+                init,
+                xor_out,
+                value: to_raw(id, init),
+            })
+        }
+    }
+
+    /// The usual CRC-32 of zlib, PNG and Ethernet.
+    pub fn crc32() -> Self {
+        Crc::new(AV_CRC_32_IEEE_LE, 0xffff_ffff, 0xffff_ffff).expect("av_crc_get_table")
+    }
+
+    pub fn value(&self) -> u32 {
+        from_raw(self.id, self.value) ^ self.xor_out
+    }
+}
+
+impl Digest for Crc {
+    fn update(&mut self, data: &[u8]) {
+        self.value = unsafe { av_crc(self.table, self.value, data.as_ptr(), data.len() as _) };
+    }
+
+    fn output_size(&self) -> usize {
+        crc_bits(self.id) as usize / 8
+    }
+
+    fn finalize_into(&mut self, out: &mut [u8]) {
+        let size = self.output_size();
+        check_output(out, size);
+        out[..size].copy_from_slice(&be_bytes(self.value(), size));
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.value = to_raw(self.id, self.init);
+    }
+}
+
+fn crc_bits(id: AVCRCId) -> u32 {
+    match id {
+        AV_CRC_8_ATM | AV_CRC_8_EBU => 8,
+        AV_CRC_16_ANSI | AV_CRC_16_CCITT | AV_CRC_16_ANSI_LE => 16,
+        AV_CRC_24_IEEE => 24,
+        _ => 32,
+    }
+}
+
+// The tables of the MSB-first CRCs are byte swapped, and so are the values
+// `av_crc` takes and returns for them
+fn to_raw(id: AVCRCId, value: u32) -> u32 {
+    match id {
+        AV_CRC_32_IEEE_LE | AV_CRC_16_ANSI_LE => value,
+        _ => (value << (32 - crc_bits(id))).swap_bytes(),
+    }
+}
+
+fn from_raw(id: AVCRCId, raw: u32) -> u32 {
+    match id {
+        AV_CRC_32_IEEE_LE | AV_CRC_16_ANSI_LE => raw,
+        _ => raw.swap_bytes() >> (32 - crc_bits(id)),
+    }
+}
+
+fn be_bytes(value: u32, size: usize) -> Vec<u8> {
+    (0..size).rev().map(|i| (value >> (8 * i)) as u8).collect()
+}
+
+// The init functions return -1 rather than an error code for unsupported sizes
+fn check_bits(ret: c_int) -> Result<(), AvError> {
+    if ret < 0 {
+        Err(AvError(AVERROR(EINVAL)))
+    } else {
+        Ok(())
+    }
+}
+
+macro_rules! digest_context {
+	($(#[$attr:meta])* $name:ident, $raw:ident, $alloc:ident) => {
+		$(#[$attr])*
+		pub struct $name {
+			ptr: *mut $raw,
+			bits: c_int,
+		}
+
+		unsafe impl Send for $name {}
+
+		impl $name {
+			fn alloc(bits: c_int) -> Self {
+				let ptr = unsafe { $alloc() };
+				assert!(!ptr.is_null(), concat!(stringify!($alloc), ": out of memory"));
+
+				$name { ptr, bits }
+			}
+		}
+
+		impl Drop for $name {
+			fn drop(&mut self) {
+				unsafe { av_free(self.ptr as *mut c_void) }
+			}
+		}
+	};
+}
+
+digest_context!(
+    /// MD5, as used by FFmpeg's `md5` and `framemd5` muxers.
+    Md5, AVMD5, av_md5_alloc
+);
+
+impl Md5 {
+    pub fn new() -> Self {
+        let md5 = Md5::alloc(128);
+        unsafe { av_md5_init(md5.ptr) }
+
+        md5
+    }
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Md5::new()
+    }
+}
+
+impl Digest for Md5 {
+    fn update(&mut self, data: &[u8]) {
+        for chunk in chunks(data) {
+            unsafe { av_md5_update(self.ptr, chunk.as_ptr(), chunk.len() as _) }
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        self.bits as usize / 8
+    }
+
+    fn finalize_into(&mut self, out: &mut [u8]) {
+        check_output(out, self.output_size());
+        unsafe { av_md5_final(self.ptr, out.as_mut_ptr()) }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        unsafe { av_md5_init(self.ptr) }
+    }
+}
+
+digest_context!(
+    /// SHA-1 or SHA-2 with 224 or 256 bits of output.
+    Sha, AVSHA, av_sha_alloc
+);
+
+impl Sha {
+    /// `bits` is 160 for SHA-1, or 224 or 256.
+    pub fn new(bits: u32) -> Result<Self, AvError> {
+        let sha = Sha::alloc(bits as c_int);
+        check_bits(unsafe { av_sha_init(sha.ptr, sha.bits) })?;
+
+        Ok(sha)
+    }
+}
+
+impl Digest for Sha {
+    fn update(&mut self, data: &[u8]) {
+        for chunk in chunks(data) {
+            unsafe { av_sha_update(self.ptr, chunk.as_ptr(), chunk.len() as _) }
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        self.bits as usize / 8
+    }
+
+    fn finalize_into(&mut self, out: &mut [u8]) {
+        check_output(out, self.output_size());
+        unsafe { av_sha_final(self.ptr, out.as_mut_ptr()) }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        unsafe { av_sha_init(self.ptr, self.bits) };
+    }
+}
+
+digest_context!(
+    /// SHA-2 with 224, 256, 384 or 512 bits of output over 64-bit words.
+    Sha512, AVSHA512, av_sha512_alloc
+);
+
+impl Sha512 {
+    /// `bits` is 224 or 256 for SHA-512/t, or 384 or 512.
+    pub fn new(bits: u32) -> Result<Self, AvError> {
+        let sha = Sha512::alloc(bits as c_int);
+        check_bits(unsafe { av_sha512_init(sha.ptr, sha.bits) })?;
+
+        Ok(sha)
+    }
+}
+
+impl Digest for Sha512 {
+    fn update(&mut self, data: &[u8]) {
+        for chunk in chunks(data) {
+            unsafe { av_sha512_update(self.ptr, chunk.as_ptr(), chunk.len() as _) }
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        self.bits as usize / 8
+    }
+
+    fn finalize_into(&mut self, out: &mut [u8]) {
+        check_output(out, self.output_size());
+        unsafe { av_sha512_final(self.ptr, out.as_mut_ptr()) }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        unsafe { av_sha512_init(self.ptr, self.bits) };
+    }
+}
+
+digest_context!(
+    /// RIPEMD with 128, 160, 256 or 320 bits of output.
+    Ripemd, AVRIPEMD, av_ripemd_alloc
+);
+
+impl Ripemd {
+    pub fn new(bits: u32) -> Result<Self, AvError> {
+        let ripemd = Ripemd::alloc(bits as c_int);
+        check_bits(unsafe { av_ripemd_init(ripemd.ptr, ripemd.bits) })?;
+
+        Ok(ripemd)
+    }
+}
+
+impl Digest for Ripemd {
+    fn update(&mut self, data: &[u8]) {
+        for chunk in chunks(data) {
+            unsafe { av_ripemd_update(self.ptr, chunk.as_ptr(), chunk.len() as _) }
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        self.bits as usize / 8
+    }
+
+    fn finalize_into(&mut self, out: &mut [u8]) {
+        check_output(out, self.output_size());
+        unsafe { av_ripemd_final(self.ptr, out.as_mut_ptr()) }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        unsafe { av_ripemd_init(self.ptr, self.bits) };
+    }
+}
+
+/// MurmurHash3, the 128-bit x64 variant.
+pub struct Murmur3 {
+    ptr: *mut AVMurMur3,
+    seed: u64,
+}
+
+unsafe impl Send for Murmur3 {}
+
+impl Murmur3 {
+    pub fn new() -> Self {
+        Murmur3::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        let ptr = unsafe { av_murmur3_alloc() };
+        assert!(!ptr.is_null(), "av_murmur3_alloc: out of memory");
+        unsafe { av_murmur3_init_seeded(ptr, seed) }
+
+        Murmur3 { ptr, seed }
+    }
+}
+
+impl Default for Murmur3 {
+    fn default() -> Self {
+        Murmur3::new()
+    }
+}
+
+impl Digest for Murmur3 {
+    fn update(&mut self, data: &[u8]) {
+        for chunk in chunks(data) {
+            unsafe { av_murmur3_update(self.ptr, chunk.as_ptr(), chunk.len() as _) }
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        16
+    }
+
+    fn finalize_into(&mut self, out: &mut [u8]) {
+        check_output(out, 16);
+        unsafe { av_murmur3_final(self.ptr, out.as_mut_ptr()) }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        unsafe { av_murmur3_init_seeded(self.ptr, self.seed) }
+    }
+}
+
+impl Drop for Murmur3 {
+    fn drop(&mut self) {
+        unsafe { av_free(self.ptr as *mut c_void) }
+    }
+}
+
+/// Any of the hashes of the `av_hash` registry, selected by name.
+pub struct HashContext {
+    ptr: *mut AVHashContext,
+}
+
+unsafe impl Send for HashContext {}
+
+impl HashContext {
+    /// A hash from `HashContext::names()`, e.g. `"SHA256"` or `"CRC32"`.
+    pub fn new(name: &str) -> Result<Self, AvError> {
+        let name = cstring(name)?;
+        let mut ptr = ptr::null_mut();
+
+        unsafe {
+            AvError::check(av_hash_alloc(&mut ptr, name.as_ptr()))?;
+            av_hash_init(ptr);
+        }
+
+        Ok(HashContext { ptr })
+    }
+
+    /// The names of the supported hashes.
+    pub fn names() -> HashNames {
+        HashNames { index: 0 }
+    }
+
+    pub fn name(&self) -> &'static str {
+        unsafe { ptr_to_str(av_hash_get_name(self.ptr)).unwrap_or("") }
+    }
+}
+
+impl Digest for HashContext {
+    fn update(&mut self, data: &[u8]) {
+        for chunk in chunks(data) {
+            unsafe { av_hash_update(self.ptr, chunk.as_ptr(), chunk.len() as _) }
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        unsafe { av_hash_get_size(self.ptr) as usize }
+    }
+
+    fn finalize_into(&mut self, out: &mut [u8]) {
+        check_output(out, self.output_size());
+        unsafe { av_hash_final(self.ptr, out.as_mut_ptr()) }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        unsafe { av_hash_init(self.ptr) }
+    }
+}
+
+impl Drop for HashContext {
+    fn drop(&mut self) {
+        unsafe { av_hash_freep(&mut self.ptr) }
+    }
+}
+
+/// Iterator over the names accepted by `HashContext::new`.
+pub struct HashNames {
+    index: c_int,
+}
+
+impl Iterator for HashNames {
+    type Item = &'static str;
+
+    fn next(&mut self) -> Option<&'static str> {
+        loop {
+            let ptr = unsafe { av_hash_names(self.index) };
+            if ptr.is_null() {
+                return None;
+            }
+            self.index += 1;
+
+            if let Some(name) = unsafe { ptr_to_str(ptr) } {
+                return Some(name);
+            }
+        }
+    }
+}
+
+/// A keyed-hash message authentication code (RFC 2104).
+pub struct Hmac {
+    ptr: *mut AVHMAC,
+    kind: AVHMACType,
+    key: Vec<u8>,
+}
+
+unsafe impl Send for Hmac {}
+
+impl Hmac {
+    pub fn new(kind: AVHMACType, key: &[u8]) -> Result<Self, AvError> {
+        if key.len() > c_uint::max_value() as usize {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let ptr = unsafe { av_hmac_alloc(kind) };
+        if ptr.is_null() {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let mut hmac = Hmac {
+            ptr,
+            kind,
+            key: key.to_vec(),
+        };
+        hmac.reset();
+
+        Ok(hmac)
+    }
+}
+
+impl Digest for Hmac {
+    fn update(&mut self, data: &[u8]) {
+        for chunk in chunks(data) {
+            unsafe { av_hmac_update(self.ptr, chunk.as_ptr(), chunk.len() as _) }
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        match self.kind {
+            AV_HMAC_MD5 => 16,
+            AV_HMAC_SHA1 => 20,
+            AV_HMAC_SHA224 => 28,
+            AV_HMAC_SHA256 => 32,
+            AV_HMAC_SHA384 => 48,
+            AV_HMAC_SHA512 => 64,
+        }
+    }
+
+    fn finalize_into(&mut self, out: &mut [u8]) {
+        let size = self.output_size();
+        check_output(out, size);
+        unsafe { av_hmac_final(self.ptr, out.as_mut_ptr(), size as c_uint) };
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        unsafe { av_hmac_init(self.ptr, self.key.as_ptr(), self.key.len() as c_uint) }
+    }
+}
+
+impl Drop for Hmac {
+    fn drop(&mut self) {
+        unsafe { av_hmac_free(self.ptr) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn digest<D: Digest>(mut digest: D, data: &[u8]) -> String {
+        digest.update(data);
+        let first = digest.finalize();
+
+        // Finalizing resets the state
+        digest.update(data);
+        assert_eq!(digest.finalize(), first);
+
+        hex(&first)
+    }
+
+    // RFC 1321
+    #[test]
+    fn md5() {
+        assert_eq!(digest(Md5::new(), b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(digest(Md5::new(), b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            digest(Md5::new(), b"message digest"),
+            "f96b697d7cb7938d525a2f31aaf161d0"
+        );
+    }
+
+    // FIPS 180-2 appendices
+    #[test]
+    fn sha() {
+        let sha = |bits| Sha::new(bits).unwrap();
+
+        assert_eq!(digest(sha(160), b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            digest(sha(224), b"abc"),
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"
+        );
+        assert_eq!(
+            digest(sha(256), b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            digest(
+                sha(256),
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            ),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert!(Sha::new(512).is_err());
+    }
+
+    #[test]
+    fn sha_streamed() {
+        let mut sha = Sha::new(160).unwrap();
+        for _ in 0..1000 {
+            sha.update(&[b'a'; 1000]);
+        }
+
+        assert_eq!(hex(&sha.finalize()), "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+    }
+
+    #[test]
+    fn sha512() {
+        let sha = |bits| Sha512::new(bits).unwrap();
+
+        assert_eq!(
+            digest(sha(384), b"abc"),
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded163\
+             1a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+        );
+        assert_eq!(
+            digest(sha(512), b"abc"),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+    }
+
+    // The RIPEMD-160 paper
+    #[test]
+    fn ripemd() {
+        let ripemd = || Ripemd::new(160).unwrap();
+
+        assert_eq!(digest(ripemd(), b""), "9c1185a5c5e9fc54612808977ee8f548b2258d31");
+        assert_eq!(digest(ripemd(), b"abc"), "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc");
+    }
+
+    #[test]
+    fn adler32() {
+        let mut adler = Adler32::new();
+        adler.update(b"Wikipedia");
+
+        assert_eq!(adler.value(), 0x11e6_0398);
+        assert_eq!(adler.finalize(), [0x11, 0xe6, 0x03, 0x98]);
+    }
+
+    // The check values of the CRC catalogue, over "123456789"
+    #[test]
+    fn crc() {
+        let crc = |id, init, xor_out| {
+            let mut crc = Crc::new(id, init, xor_out).unwrap();
+            crc.update(b"123456789");
+            crc.value()
+        };
+
+        assert_eq!(crc(AV_CRC_8_ATM, 0, 0), 0xf4);
+        assert_eq!(crc(AV_CRC_8_EBU, 0, 0), 0x37);
+        assert_eq!(crc(AV_CRC_16_ANSI, 0, 0), 0xfee8);
+        assert_eq!(crc(AV_CRC_16_CCITT, 0xffff, 0), 0x29b1);
+        assert_eq!(crc(AV_CRC_16_ANSI_LE, 0, 0), 0xbb3d);
+        assert_eq!(crc(AV_CRC_24_IEEE, 0xb7_04ce, 0), 0x21_cf02);
+        assert_eq!(crc(AV_CRC_32_IEEE, 0xffff_ffff, 0), 0x0376_e6e7);
+        assert_eq!(crc(AV_CRC_32_IEEE, 0xffff_ffff, 0xffff_ffff), 0xfc89_1918);
+
+        assert_eq!(Crc::new(AV_CRC_MAX, 0, 0).err(), Some(AvError(AVERROR(EINVAL))));
+
+        let mut crc32 = Crc::crc32();
+        crc32.update(b"123456789");
+        assert_eq!(crc32.finalize(), [0xcb, 0xf4, 0x39, 0x26]);
+    }
+
+    // RFC 2202 and RFC 4231, test case 2
+    #[test]
+    fn hmac() {
+        let hmac = |kind| Hmac::new(kind, b"Jefe").unwrap();
+        let data = b"what do ya want for nothing?";
+
+        assert_eq!(digest(hmac(AV_HMAC_MD5), data), "750c783e6ab0b503eaa86e310a5db738");
+        assert_eq!(
+            digest(hmac(AV_HMAC_SHA1), data),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+        assert_eq!(
+            digest(hmac(AV_HMAC_SHA256), data),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            digest(hmac(AV_HMAC_SHA512), data),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+
+    #[test]
+    fn hash_context() {
+        assert!(HashContext::names().any(|name| name == "SHA256"));
+
+        let context = HashContext::new("SHA256").unwrap();
+        assert_eq!(context.name(), "SHA256");
+        assert_eq!(
+            digest(context, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(HashContext::new("nope").is_err());
+    }
+}
@@ -0,0 +1,410 @@
+use std::{ptr, slice};
+
+use libc::{c_int, c_void, EINVAL};
+use {av_freep, av_image_alloc, av_image_check_size, av_image_copy, av_image_copy_to_buffer,
+     av_image_fill_arrays, av_image_fill_black, av_image_get_buffer_size, av_pix_fmt_count_planes,
+     av_pix_fmt_desc_get, plane_size, AVColorRange, AVPixelFormat, AvError, VideoFrame, AVERROR,
+     AV_PIX_FMT_FLAG_PSEUDOPAL};
+
+/// An image allocated with `av_image_alloc`, all planes in a single block.
+pub struct Image {
+    data: [*mut u8; 4],
+    linesize: [c_int; 4],
+    format: AVPixelFormat,
+    width: u32,
+    height: u32,
+    align: usize,
+}
+
+unsafe impl Send for Image {}
+unsafe impl Sync for Image {}
+
+impl Image {
+    /// A zeroed image with line sizes aligned to `align` bytes.
+    ///
+    /// Here and elsewhere, `align` must be a power of two.
+    pub fn new(
+        format: AVPixelFormat,
+        width: u32,
+        height: u32,
+        align: usize,
+    ) -> Result<Self, AvError> {
+        let c_align = checked_align(align)?;
+        let mut image = Image {
+            data: [ptr::null_mut(); 4],
+            linesize: [0; 4],
+            format,
+            width,
+            height,
+            align,
+        };
+
+        unsafe {
+            AvError::check(av_image_check_size(width, height, 0, ptr::null_mut()))?;
+            AvError::check(av_image_alloc(
+                image.data.as_mut_ptr(),
+                image.linesize.as_mut_ptr(),
+                width as c_int,
+                height as c_int,
+                format,
+                c_align,
+            ))?;
+        }
+
+        // The planes can be read through safe code before being written,
+        // unlike the palette, which is already set
+        for index in 0..image.planes() {
+            if let Some(len) = image.plane_len(index) {
+                unsafe { ptr::write_bytes(image.data[index], 0, len) };
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Copies an image from a contiguous buffer laid out as by
+    /// `copy_to_buffer` with the same `align`.
+    pub fn from_buffer(
+        format: AVPixelFormat,
+        width: u32,
+        height: u32,
+        align: usize,
+        src: &[u8],
+    ) -> Result<Self, AvError> {
+        if src.len() < Image::buffer_size(format, width, height, align)? {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let mut image = Image::new(format, width, height, align)?;
+        let align = checked_align(align)?;
+        let mut data = [ptr::null_mut(); 4];
+        let mut linesize = [0; 4];
+
+        unsafe {
+            AvError::check(av_image_fill_arrays(
+                data.as_mut_ptr(),
+                linesize.as_mut_ptr(),
+                src.as_ptr(),
+                format,
+                width as c_int,
+                height as c_int,
+                align,
+            ))?;
+
+            // Pseudo-paletted formats get a palette pointer past the end of
+            // the buffer, which doesn't hold one
+            let desc = av_pix_fmt_desc_get(format);
+            if !desc.is_null() && (*desc).flags & AV_PIX_FMT_FLAG_PSEUDOPAL as u64 != 0 {
+                data[1] = ptr::null_mut();
+            }
+
+            av_image_copy(
+                image.data.as_mut_ptr(),
+                image.linesize.as_mut_ptr(),
+                data.as_mut_ptr() as *mut *const u8,
+                linesize.as_ptr(),
+                format,
+                width as c_int,
+                height as c_int,
+            );
+        }
+
+        Ok(image)
+    }
+
+    /// Size in bytes of a contiguous buffer holding such an image.
+    pub fn buffer_size(
+        format: AVPixelFormat,
+        width: u32,
+        height: u32,
+        align: usize,
+    ) -> Result<usize, AvError> {
+        let align = checked_align(align)?;
+
+        unsafe {
+            AvError::check(av_image_get_buffer_size(
+                format,
+                width as c_int,
+                height as c_int,
+                align,
+            )).map(|size| size as usize)
+        }
+    }
+
+    pub fn format(&self) -> AVPixelFormat {
+        self.format
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn align(&self) -> usize {
+        self.align
+    }
+
+    /// Number of planes of the pixel format, not counting the palette.
+    pub fn planes(&self) -> usize {
+        let count = unsafe { av_pix_fmt_count_planes(self.format) };
+
+        if count < 0 {
+            0
+        } else {
+            count as usize
+        }
+    }
+
+    /// Size in bytes of a line of the plane, including padding.
+    pub fn stride(&self, index: usize) -> usize {
+        self.linesize[index] as usize
+    }
+
+    pub fn plane(&self, index: usize) -> Option<&[u8]> {
+        let len = self.plane_len(index)?;
+
+        unsafe { Some(slice::from_raw_parts(self.data[index], len)) }
+    }
+
+    pub fn plane_mut(&mut self, index: usize) -> Option<&mut [u8]> {
+        let len = self.plane_len(index)?;
+
+        unsafe { Some(slice::from_raw_parts_mut(self.data[index], len)) }
+    }
+
+    /// Copies the planes into `dst` back to back, with lines aligned to
+    /// `align`, returning the number of bytes written.
+    pub fn copy_to_buffer(&self, dst: &mut [u8], align: usize) -> Result<usize, AvError> {
+        let align = checked_align(align)?;
+        let size = if dst.len() > c_int::max_value() as usize {
+            c_int::max_value()
+        } else {
+            dst.len() as c_int
+        };
+
+        unsafe {
+            AvError::check(av_image_copy_to_buffer(
+                dst.as_mut_ptr(),
+                size,
+                self.data.as_ptr() as *const *const u8,
+                self.linesize.as_ptr(),
+                self.format,
+                self.width as c_int,
+                self.height as c_int,
+                align,
+            )).map(|size| size as usize)
+        }
+    }
+
+    pub fn to_vec(&self, align: usize) -> Result<Vec<u8>, AvError> {
+        let mut buffer = vec![0; Image::buffer_size(self.format, self.width, self.height, align)?];
+        let size = self.copy_to_buffer(&mut buffer, align)?;
+        buffer.truncate(size);
+
+        Ok(buffer)
+    }
+
+    /// Copies the data of a frame with the same format and dimensions.
+    pub fn copy_from_frame(&mut self, frame: &VideoFrame) -> Result<(), AvError> {
+        if !self.matches(frame) {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        unsafe {
+            av_image_copy(
+                self.data.as_mut_ptr(),
+                self.linesize.as_mut_ptr(),
+                (*frame.as_ptr()).data.as_ptr() as *mut *const u8,
+                (*frame.as_ptr()).linesize.as_ptr(),
+                self.format,
+                self.width as c_int,
+                self.height as c_int,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Copies the data into a frame with the same format and dimensions,
+    /// making it writable first.
+    pub fn copy_to_frame(&self, frame: &mut VideoFrame) -> Result<(), AvError> {
+        if !self.matches(frame) {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+        frame.make_writable()?;
+
+        unsafe {
+            let frame = frame.as_mut_ptr();
+
+            av_image_copy(
+                (*frame).data.as_mut_ptr(),
+                (*frame).linesize.as_mut_ptr(),
+                self.data.as_ptr() as *mut *const u8,
+                self.linesize.as_ptr(),
+                self.format,
+                self.width as c_int,
+                self.height as c_int,
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn to_frame(&self) -> Result<VideoFrame, AvError> {
+        let mut frame = VideoFrame::new(self.format, self.width, self.height)?;
+        self.copy_to_frame(&mut frame)?;
+
+        Ok(frame)
+    }
+
+    /// Overwrites the image with black, as defined by the color range for
+    /// YUV formats.
+    pub fn fill_black(&mut self, range: AVColorRange) -> Result<(), AvError> {
+        let linesize = [
+            self.linesize[0] as _,
+            self.linesize[1] as _,
+            self.linesize[2] as _,
+            self.linesize[3] as _,
+        ];
+
+        unsafe {
+            AvError::check(av_image_fill_black(
+                self.data.as_mut_ptr(),
+                linesize.as_ptr(),
+                self.format,
+                range,
+                self.width as c_int,
+                self.height as c_int,
+            )).map(|_| ())
+        }
+    }
+
+    fn matches(&self, frame: &VideoFrame) -> bool {
+        frame.format() == Some(self.format)
+            && frame.width() == self.width
+            && frame.height() == self.height
+    }
+
+    fn plane_len(&self, index: usize) -> Option<usize> {
+        if index >= self.planes() || self.data[index].is_null() {
+            return None;
+        }
+
+        plane_size(self.format, self.height, index, self.linesize[index])
+    }
+}
+
+/// Alignments of zero would give empty lines, and others that aren't powers
+/// of two break the rounding in imgutils.
+fn checked_align(align: usize) -> Result<c_int, AvError> {
+    if !align.is_power_of_two() || align > c_int::max_value() as usize {
+        Err(AvError(AVERROR(EINVAL)))
+    } else {
+        Ok(align as c_int)
+    }
+}
+
+impl Clone for Image {
+    fn clone(&self) -> Self {
+        let mut image = Image::new(self.format, self.width, self.height, self.align)
+            .expect("av_image_alloc");
+
+        unsafe {
+            av_image_copy(
+                image.data.as_mut_ptr(),
+                image.linesize.as_mut_ptr(),
+                self.data.as_ptr() as *mut *const u8,
+                self.linesize.as_ptr(),
+                self.format,
+                self.width as c_int,
+                self.height as c_int,
+            );
+        }
+
+        image
+    }
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        unsafe {
+            av_freep(&mut self.data[0] as *mut *mut u8 as *mut c_void);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+    use {av_pix_fmt_desc_get_id, av_pix_fmt_desc_next, AV_PIX_FMT_FLAG_HWACCEL,
+         AV_PIX_FMT_FLAG_PAL};
+
+    #[test]
+    fn layout_matches_buffer_size() {
+        // Odd dimensions, so subsampled planes have to be rounded up, and
+        // the minimum alignment for paletted formats
+        let (width, height, align) = (17, 9, 4);
+        let mut desc = ptr::null();
+
+        loop {
+            desc = unsafe { av_pix_fmt_desc_next(desc) };
+            if desc.is_null() {
+                break;
+            }
+
+            let flags = unsafe { (*desc).flags };
+            if flags & AV_PIX_FMT_FLAG_HWACCEL as u64 != 0 {
+                continue;
+            }
+
+            let format = unsafe { av_pix_fmt_desc_get_id(desc) };
+            let size = ok(format, Image::buffer_size(format, width, height, align));
+            let image = ok(format, Image::new(format, width, height, align));
+
+            let buffer = ok(format, image.to_vec(align));
+            assert_eq!(buffer.len(), size, "{:?}", format);
+
+            // The palette is not a plane
+            if flags & (AV_PIX_FMT_FLAG_PAL | AV_PIX_FMT_FLAG_PSEUDOPAL) as u64 == 0 {
+                let planes = (0..image.planes())
+                    .map(|index| image.plane(index).unwrap().len())
+                    .sum::<usize>();
+
+                assert_eq!(planes, size, "{:?}", format);
+            }
+
+            let copy = ok(format, Image::from_buffer(format, width, height, align, &buffer));
+            assert_eq!(ok(format, copy.to_vec(align)), buffer, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_alignment() {
+        let format = AVPixelFormat::AV_PIX_FMT_GRAY8;
+        let image = Image::new(format, 17, 9, 4).unwrap();
+        let mut buffer = image.to_vec(4).unwrap();
+        let einval = Some(AvError(AVERROR(EINVAL)));
+
+        for &align in &[0, 3, 24, c_int::max_value() as usize + 1] {
+            assert_eq!(Image::new(format, 17, 9, align).err(), einval, "{}", align);
+            assert_eq!(Image::buffer_size(format, 17, 9, align).err(), einval, "{}", align);
+            assert_eq!(
+                Image::from_buffer(format, 17, 9, align, &buffer).err(),
+                einval,
+                "{}",
+                align
+            );
+            assert_eq!(image.copy_to_buffer(&mut buffer, align).err(), einval, "{}", align);
+            assert_eq!(image.to_vec(align).err(), einval, "{}", align);
+        }
+    }
+
+    fn ok<T>(format: AVPixelFormat, result: Result<T, AvError>) -> T {
+        result.unwrap_or_else(|err| panic!("{:?}: {}", format, err))
+    }
+}
@@ -0,0 +1,230 @@
+use libc::c_int;
+use {av_log_get_level, av_log_set_level};
+use {AV_LOG_DEBUG, AV_LOG_ERROR, AV_LOG_FATAL, AV_LOG_INFO, AV_LOG_PANIC, AV_LOG_QUIET,
+     AV_LOG_TRACE, AV_LOG_VERBOSE, AV_LOG_WARNING};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LogLevel {
+    Quiet,
+    Panic,
+    Fatal,
+    Error,
+    Warning,
+    Info,
+    Verbose,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// Maps an `AV_LOG_*` value, rounding down to the closest named level.
+    pub fn from_raw(level: c_int) -> LogLevel {
+        match level {
+            l if l >= AV_LOG_TRACE => LogLevel::Trace,
+            l if l >= AV_LOG_DEBUG => LogLevel::Debug,
+            l if l >= AV_LOG_VERBOSE => LogLevel::Verbose,
+            l if l >= AV_LOG_INFO => LogLevel::Info,
+            l if l >= AV_LOG_WARNING => LogLevel::Warning,
+            l if l >= AV_LOG_ERROR => LogLevel::Error,
+            l if l >= AV_LOG_FATAL => LogLevel::Fatal,
+            l if l >= AV_LOG_PANIC => LogLevel::Panic,
+            _ => LogLevel::Quiet,
+        }
+    }
+
+    pub fn as_raw(self) -> c_int {
+        match self {
+            LogLevel::Quiet => AV_LOG_QUIET,
+            LogLevel::Panic => AV_LOG_PANIC,
+            LogLevel::Fatal => AV_LOG_FATAL,
+            LogLevel::Error => AV_LOG_ERROR,
+            LogLevel::Warning => AV_LOG_WARNING,
+            LogLevel::Info => AV_LOG_INFO,
+            LogLevel::Verbose => AV_LOG_VERBOSE,
+            LogLevel::Debug => AV_LOG_DEBUG,
+            LogLevel::Trace => AV_LOG_TRACE,
+        }
+    }
+}
+
+/// Messages above this level are dropped before being formatted.
+pub fn set_log_level(level: LogLevel) {
+    unsafe { av_log_set_level(level.as_raw()) }
+}
+
+pub fn log_level() -> LogLevel {
+    LogLevel::from_raw(unsafe { av_log_get_level() })
+}
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub use self::bridge::*;
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+mod bridge {
+    use std::cell::RefCell;
+    use std::ffi::CStr;
+    use std::panic::{self, AssertUnwindSafe};
+
+    use libc::{c_char, c_int, c_void};
+    use {ptr_to_str, AVClass, LogLevel};
+
+    extern "C" {
+        fn ffmpeg_sys_log_set_callback(
+            cb: Option<unsafe extern "C" fn(*mut c_void, c_int, *const c_char)>,
+        );
+    }
+
+    thread_local! {
+        // FFmpeg may emit a line in several calls, buffered until the newline
+        static PENDING: RefCell<(String, String)> = RefCell::new((String::new(), String::new()));
+    }
+
+    /// Replaces FFmpeg's default stderr logging with forwarding to `log`
+    /// and to `tracing`, for whichever of the two features are enabled.
+    ///
+    /// Messages are emitted under the `ffmpeg` target, with the `AVClass`
+    /// item name of their source, e.g. `h264`, as a `[h264]` prefix for
+    /// `log` and as the `context` field of events for `tracing`.
+    pub fn install_log_bridge() {
+        unsafe { ffmpeg_sys_log_set_callback(Some(callback)) }
+    }
+
+    /// Restores `av_log_default_callback`.
+    pub fn remove_log_bridge() {
+        unsafe { ffmpeg_sys_log_set_callback(None) }
+    }
+
+    unsafe extern "C" fn callback(avcl: *mut c_void, level: c_int, line: *const c_char) {
+        let line = CStr::from_ptr(line).to_string_lossy();
+
+        let _ = PENDING.try_with(|pending| {
+            let mut pending = match pending.try_borrow_mut() {
+                Ok(pending) => pending,
+                Err(_) => return,
+            };
+            let (ref mut context, ref mut message) = *pending;
+
+            if message.is_empty() {
+                context.clear();
+                context.push_str(item_name(avcl).unwrap_or(""));
+            }
+            message.push_str(&line);
+
+            if message.ends_with('\n') {
+                // A panicking logger must not unwind into C, the line is lost
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                    emit(LogLevel::from_raw(level), context, message.trim_end())
+                }));
+                message.clear();
+            }
+        });
+    }
+
+    unsafe fn item_name<'a>(avcl: *mut c_void) -> Option<&'a str> {
+        if avcl.is_null() {
+            return None;
+        }
+
+        let class = *(avcl as *const *const AVClass);
+        if class.is_null() {
+            return None;
+        }
+
+        match (*class).item_name {
+            Some(item_name) => ptr_to_str(item_name(avcl)),
+            None => ptr_to_str((*class).class_name),
+        }
+    }
+
+    fn emit(level: LogLevel, context: &str, message: &str) {
+        #[cfg(feature = "log")]
+        emit_log(level, context, message);
+        #[cfg(feature = "tracing")]
+        emit_tracing(level, context, message);
+    }
+
+    #[cfg(feature = "tracing")]
+    fn emit_tracing(level: LogLevel, context: &str, message: &str) {
+        use tracing::Level;
+
+        match level {
+            LogLevel::Quiet => (),
+            LogLevel::Panic | LogLevel::Fatal | LogLevel::Error => {
+                event!(target: "ffmpeg", Level::ERROR, context, "{}", message)
+            }
+            LogLevel::Warning => event!(target: "ffmpeg", Level::WARN, context, "{}", message),
+            LogLevel::Info => event!(target: "ffmpeg", Level::INFO, context, "{}", message),
+            LogLevel::Verbose | LogLevel::Debug => {
+                event!(target: "ffmpeg", Level::DEBUG, context, "{}", message)
+            }
+            LogLevel::Trace => event!(target: "ffmpeg", Level::TRACE, context, "{}", message),
+        }
+    }
+
+    #[cfg(feature = "log")]
+    fn emit_log(level: LogLevel, context: &str, message: &str) {
+        use log::Level;
+
+        let level = match level {
+            LogLevel::Quiet => return,
+            LogLevel::Panic | LogLevel::Fatal | LogLevel::Error => Level::Error,
+            LogLevel::Warning => Level::Warn,
+            LogLevel::Info => Level::Info,
+            LogLevel::Verbose | LogLevel::Debug => Level::Debug,
+            LogLevel::Trace => Level::Trace,
+        };
+
+        if context.is_empty() {
+            log!(target: "ffmpeg", level, "{}", message);
+        } else {
+            log!(target: "ffmpeg", level, "[{}] {}", context, message);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "log"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use log::{self, Level, LevelFilter, Log, Metadata, Record};
+    use {install_log_bridge, Expr};
+
+    struct Capture(Mutex<Vec<(Level, String)>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            if record.target() == "ffmpeg" {
+                let line = record.args().to_string();
+                self.0.lock().unwrap().push((record.level(), line));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+    #[test]
+    fn forwards_messages_to_log() {
+        log::set_logger(&CAPTURE).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+        install_log_bridge();
+
+        // Logs "Undefined constant or missing '(' in 'undefined_constant'"
+        assert!(Expr::parse("undefined_constant", &[]).is_err());
+
+        let lines = CAPTURE.0.lock().unwrap();
+        assert!(
+            lines
+                .iter()
+                .any(|&(level, ref line)| level == Level::Error
+                    && line.contains("'undefined_constant'")),
+            "{:?}",
+            *lines
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::error;
+use std::fmt;
+
+use libc::{c_int, c_void};
+use {av_lzo1x_decode, AV_LZO_ERROR, AV_LZO_INPUT_DEPLETED, AV_LZO_INPUT_PADDING,
+     AV_LZO_INVALID_BACKPTR, AV_LZO_OUTPUT_FULL, AV_LZO_OUTPUT_PADDING};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LzoError {
+    /// The input ended in the middle of the stream.
+    InputDepleted,
+    /// The output slice is too small for the decompressed data.
+    OutputFull,
+    /// A back reference points before the start of the output.
+    InvalidBackReference,
+    /// The input is not valid LZO1X data, or too large.
+    Invalid,
+}
+
+impl fmt::Display for LzoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            LzoError::InputDepleted => "LZO input depleted",
+            LzoError::OutputFull => "LZO output full",
+            LzoError::InvalidBackReference => "invalid LZO back reference",
+            LzoError::Invalid => "invalid LZO data",
+        })
+    }
+}
+
+impl error::Error for LzoError {
+    fn description(&self) -> &str {
+        "LZO decoding failed"
+    }
+}
+
+/// Decompresses LZO1X data from `input` into `output`, returning the
+/// number of bytes written.
+///
+/// FFmpeg reads and writes a few bytes past the end of its buffers, so both
+/// sides go through padded copies. On error, `output` still holds whatever
+/// was decoded.
+pub fn lzo1x_decode(input: &[u8], output: &mut [u8]) -> Result<usize, LzoError> {
+    if input.len() > c_int::max_value() as usize || output.len() > c_int::max_value() as usize {
+        return Err(LzoError::Invalid);
+    }
+
+    let mut padded_in = Vec::with_capacity(input.len() + AV_LZO_INPUT_PADDING as usize);
+    padded_in.extend_from_slice(input);
+    padded_in.resize(input.len() + AV_LZO_INPUT_PADDING as usize, 0);
+    let mut padded_out = vec![0u8; output.len() + AV_LZO_OUTPUT_PADDING as usize];
+
+    let mut in_len = input.len() as c_int;
+    let mut out_len = output.len() as c_int;
+
+    let ret = unsafe {
+        av_lzo1x_decode(
+            padded_out.as_mut_ptr() as *mut c_void,
+            &mut out_len,
+            padded_in.as_ptr() as *const c_void,
+            &mut in_len,
+        )
+    };
+
+    // The lengths are updated to what is left over
+    let written = output.len() - out_len as usize;
+    output[..written].copy_from_slice(&padded_out[..written]);
+
+    if ret & AV_LZO_ERROR != 0 {
+        Err(LzoError::Invalid)
+    } else if ret & AV_LZO_INVALID_BACKPTR != 0 {
+        Err(LzoError::InvalidBackReference)
+    } else if ret & AV_LZO_OUTPUT_FULL != 0 {
+        Err(LzoError::OutputFull)
+    } else if ret & AV_LZO_INPUT_DEPLETED != 0 {
+        Err(LzoError::InputDepleted)
+    } else {
+        Ok(written)
+    }
+}
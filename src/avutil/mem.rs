@@ -0,0 +1,330 @@
+use std::cmp;
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::{ptr, slice};
+
+use libc::{c_void, EINVAL, ENOMEM};
+use {av_free, av_malloc, av_mallocz, av_max_alloc, av_realloc, AvError, AVERROR};
+
+/// `AV_INPUT_BUFFER_PADDING_SIZE` of libavcodec: zeroed bytes required past
+/// the end of data handed to decoders and parsers, e.g. extradata.
+#[cfg(feature = "avcodec")]
+pub const INPUT_BUFFER_PADDING_SIZE: usize = ::AV_INPUT_BUFFER_PADDING_SIZE as usize;
+
+/// `AV_INPUT_BUFFER_PADDING_SIZE` of libavcodec, its value since FFmpeg 4.0
+/// as the header is not bound without the `avcodec` feature.
+#[cfg(not(feature = "avcodec"))]
+pub const INPUT_BUFFER_PADDING_SIZE: usize = 64;
+
+// The alignment `av_malloc` guarantees on every platform.
+const MAX_ALIGN: usize = 16;
+
+/// Caps the size of any single allocation through `av_malloc` and friends,
+/// `INT_MAX` by default.
+pub fn set_max_alloc(max: usize) {
+    unsafe { av_max_alloc(max as _) }
+}
+
+/// A value in memory from `av_malloc`, which FFmpeg may take ownership of
+/// and free with `av_free`.
+pub struct AvBox<T: ?Sized> {
+    ptr: *mut T,
+}
+
+unsafe impl<T: ?Sized + Send> Send for AvBox<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for AvBox<T> {}
+
+impl<T> AvBox<T> {
+    /// Fails with `EINVAL` for types aligned to more than 16 bytes.
+    pub fn new(value: T) -> Result<Self, AvError> {
+        if mem::align_of::<T>() > MAX_ALIGN {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let ptr = unsafe { av_malloc(mem::size_of::<T>() as _) } as *mut T;
+        if ptr.is_null() {
+            return Err(AvError(AVERROR(ENOMEM)));
+        }
+
+        unsafe { ptr::write(ptr, value) };
+
+        Ok(AvBox { ptr })
+    }
+
+    /// Takes ownership of a value allocated with `av_malloc`.
+    pub unsafe fn from_raw(ptr: *mut T) -> Self {
+        AvBox { ptr }
+    }
+
+    /// Gives up ownership, the value must be freed with `av_free`.
+    pub fn into_raw(self) -> *mut T {
+        let ptr = self.ptr;
+        mem::forget(self);
+
+        ptr
+    }
+}
+
+impl AvBox<[u8]> {
+    /// `len` zeroed bytes, followed by `INPUT_BUFFER_PADDING_SIZE` zeroed
+    /// bytes of padding.
+    pub fn zeroed(len: usize) -> Result<Self, AvError> {
+        let data = unsafe { av_mallocz(padded(len)? as _) } as *mut u8;
+
+        if data.is_null() {
+            Err(AvError(AVERROR(ENOMEM)))
+        } else {
+            Ok(unsafe { AvBox::from_raw_parts(data, len) })
+        }
+    }
+
+    /// A padded copy of `data`.
+    pub fn from_slice(data: &[u8]) -> Result<Self, AvError> {
+        let mut boxed = AvBox::zeroed(data.len())?;
+        boxed.copy_from_slice(data);
+
+        Ok(boxed)
+    }
+
+    /// Takes ownership of `len` bytes allocated with `av_malloc`.
+    pub unsafe fn from_raw_parts(data: *mut u8, len: usize) -> Self {
+        AvBox {
+            ptr: ptr::slice_from_raw_parts_mut(data, len),
+        }
+    }
+
+    /// Gives up ownership, e.g. to set `AVCodecParameters.extradata` and
+    /// `extradata_size`.
+    pub fn into_raw_parts(self) -> (*mut u8, usize) {
+        let len = self.len();
+        (self.into_raw_slice() as *mut u8, len)
+    }
+
+    fn into_raw_slice(self) -> *mut [u8] {
+        let ptr = self.ptr;
+        mem::forget(self);
+
+        ptr
+    }
+}
+
+impl<T: ?Sized> Deref for AvBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.ptr }
+    }
+}
+
+impl<T: ?Sized> DerefMut for AvBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.ptr }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for AvBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> Drop for AvBox<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.ptr);
+            av_free(self.ptr as *mut c_void);
+        }
+    }
+}
+
+/// A growable byte buffer in memory from `av_malloc`, always followed by
+/// `INPUT_BUFFER_PADDING_SIZE` zeroed bytes so it can be handed to FFmpeg
+/// as is.
+pub struct AvVec {
+    data: *mut u8,
+    len: usize,
+    capacity: usize,
+}
+
+unsafe impl Send for AvVec {}
+unsafe impl Sync for AvVec {}
+
+impl AvVec {
+    pub fn new() -> Result<Self, AvError> {
+        AvVec::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Result<Self, AvError> {
+        let data = unsafe { av_mallocz(padded(capacity)? as _) } as *mut u8;
+
+        if data.is_null() {
+            Err(AvError(AVERROR(ENOMEM)))
+        } else {
+            Ok(AvVec {
+                data,
+                len: 0,
+                capacity,
+            })
+        }
+    }
+
+    pub fn from_slice(data: &[u8]) -> Result<Self, AvError> {
+        let mut vec = AvVec::with_capacity(data.len())?;
+        vec.extend_from_slice(data)?;
+
+        Ok(vec)
+    }
+
+    /// Takes ownership of `len` bytes allocated with `av_malloc`, followed
+    /// by `INPUT_BUFFER_PADDING_SIZE` zeroed bytes.
+    pub unsafe fn from_raw(data: *mut u8, len: usize) -> Self {
+        AvVec {
+            data,
+            len,
+            capacity: len,
+        }
+    }
+
+    /// Gives up ownership of the padded data, which must be freed with
+    /// `av_free`.
+    pub fn into_raw(self) -> (*mut u8, usize) {
+        let parts = (self.data, self.len);
+        mem::forget(self);
+
+        parts
+    }
+
+    pub fn into_boxed_slice(self) -> AvBox<[u8]> {
+        let (data, len) = self.into_raw();
+        unsafe { AvBox::from_raw_parts(data, len) }
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.data
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of bytes that fit without reallocating, excluding padding.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Makes room for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: usize) -> Result<(), AvError> {
+        let required = self.len
+            .checked_add(additional)
+            .ok_or_else(|| AvError(AVERROR(ENOMEM)))?;
+        if required <= self.capacity {
+            return Ok(());
+        }
+
+        let capacity = cmp::max(required, self.capacity.saturating_mul(2));
+        let data = unsafe { av_realloc(self.data as *mut c_void, padded(capacity)? as _) };
+        if data.is_null() {
+            return Err(AvError(AVERROR(ENOMEM)));
+        }
+
+        self.data = data as *mut u8;
+        self.capacity = capacity;
+
+        Ok(())
+    }
+
+    pub fn push(&mut self, value: u8) -> Result<(), AvError> {
+        self.extend_from_slice(&[value])
+    }
+
+    pub fn extend_from_slice(&mut self, data: &[u8]) -> Result<(), AvError> {
+        self.reserve(data.len())?;
+
+        unsafe {
+            ptr::copy_nonoverlapping(data.as_ptr(), self.data.add(self.len), data.len());
+            self.set_len(self.len + data.len());
+        }
+
+        Ok(())
+    }
+
+    /// Grows to `len` bytes filled with `value`, or truncates to `len`.
+    pub fn resize(&mut self, len: usize, value: u8) -> Result<(), AvError> {
+        if len > self.len {
+            self.reserve(len - self.len)?;
+            unsafe { ptr::write_bytes(self.data.add(self.len), value, len - self.len) };
+        }
+
+        unsafe { self.set_len(len) };
+
+        Ok(())
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            unsafe { self.set_len(len) }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    // Moves the end of the data, zeroing the padding after it
+    unsafe fn set_len(&mut self, len: usize) {
+        self.len = len;
+        ptr::write_bytes(self.data.add(len), 0, INPUT_BUFFER_PADDING_SIZE);
+    }
+}
+
+impl Deref for AvVec {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.data, self.len) }
+    }
+}
+
+impl DerefMut for AvVec {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.data, self.len) }
+    }
+}
+
+impl AsRef<[u8]> for AvVec {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for AvVec {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl fmt::Debug for AvVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl Drop for AvVec {
+    fn drop(&mut self) {
+        unsafe { av_free(self.data as *mut c_void) }
+    }
+}
+
+fn padded(len: usize) -> Result<usize, AvError> {
+    len.checked_add(INPUT_BUFFER_PADDING_SIZE)
+        .ok_or_else(|| AvError(AVERROR(ENOMEM)))
+}
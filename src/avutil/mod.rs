@@ -12,3 +12,74 @@ pub use self::rational::*;
 
 mod pixfmt;
 pub use self::pixfmt::*;
+
+mod samplefmt;
+pub use self::samplefmt::*;
+
+mod channel_layout;
+pub use self::channel_layout::*;
+
+mod dict;
+pub use self::dict::*;
+
+mod opt;
+pub use self::opt::*;
+
+mod log;
+pub use self::log::*;
+
+mod buffer;
+pub use self::buffer::*;
+
+mod frame;
+pub use self::frame::*;
+
+mod side_data;
+pub use self::side_data::*;
+
+mod image;
+pub use self::image::*;
+
+mod audio_fifo;
+pub use self::audio_fifo::*;
+
+mod hash;
+pub use self::hash::*;
+
+mod cipher;
+pub use self::cipher::*;
+
+mod eval;
+pub use self::eval::*;
+
+mod parseutils;
+pub use self::parseutils::*;
+
+mod timecode;
+pub use self::timecode::*;
+
+mod threadmessage;
+pub use self::threadmessage::*;
+
+mod fifo;
+pub use self::fifo::*;
+
+mod cpu;
+pub use self::cpu::*;
+
+mod bprint;
+pub use self::bprint::*;
+
+pub mod base64;
+
+mod lzo;
+pub use self::lzo::*;
+
+mod random;
+pub use self::random::*;
+
+mod mem;
+pub use self::mem::*;
+
+mod file;
+pub use self::file::*;
@@ -0,0 +1,456 @@
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::ptr;
+
+use libc::{c_char, c_int, c_void};
+use AVOptionType::*;
+use {av_free, av_opt_find2, av_opt_get, av_opt_get_channel_layout, av_opt_get_dict_val,
+     av_opt_get_double, av_opt_get_image_size, av_opt_get_int, av_opt_get_pixel_fmt,
+     av_opt_get_q, av_opt_get_sample_fmt, av_opt_get_video_rate, av_opt_next, av_opt_set,
+     av_opt_set_bin, av_opt_set_channel_layout, av_opt_set_defaults, av_opt_set_dict2,
+     av_opt_set_dict_val, av_opt_set_double, av_opt_set_image_size, av_opt_set_int,
+     av_opt_set_pixel_fmt, av_opt_set_q, av_opt_set_sample_fmt, av_opt_set_video_rate,
+     cstring, ptr_to_str};
+use {AVOption, AVOptionType, AVPixelFormat, AVRational, AVSampleFormat, AvError, ChannelLayout,
+     Dictionary, SampleFormat};
+use {AV_OPT_FLAG_AUDIO_PARAM, AV_OPT_FLAG_BSF_PARAM, AV_OPT_FLAG_DECODING_PARAM,
+     AV_OPT_FLAG_ENCODING_PARAM, AV_OPT_FLAG_EXPORT, AV_OPT_FLAG_FILTERING_PARAM,
+     AV_OPT_FLAG_READONLY, AV_OPT_FLAG_SUBTITLE_PARAM, AV_OPT_FLAG_VIDEO_PARAM,
+     AV_OPT_SEARCH_CHILDREN};
+
+bitflags! {
+    pub struct OptionFlags: c_int {
+        const ENCODING_PARAM  = AV_OPT_FLAG_ENCODING_PARAM;
+        const DECODING_PARAM  = AV_OPT_FLAG_DECODING_PARAM;
+        const AUDIO_PARAM     = AV_OPT_FLAG_AUDIO_PARAM;
+        const VIDEO_PARAM     = AV_OPT_FLAG_VIDEO_PARAM;
+        const SUBTITLE_PARAM  = AV_OPT_FLAG_SUBTITLE_PARAM;
+        const EXPORT          = AV_OPT_FLAG_EXPORT;
+        const READONLY        = AV_OPT_FLAG_READONLY;
+        const BSF_PARAM       = AV_OPT_FLAG_BSF_PARAM;
+        const FILTERING_PARAM = AV_OPT_FLAG_FILTERING_PARAM;
+    }
+}
+
+/// The default value of an option, as stored in its `AVOption`.
+#[derive(Copy, Clone, Debug)]
+pub enum OptionDefault<'a> {
+    Int(i64),
+    Double(f64),
+    Rational(AVRational),
+    String(Option<&'a str>),
+}
+
+/// Describes a single `AVOption` of an object.
+#[derive(Copy, Clone)]
+pub struct OptionDescriptor<'a> {
+    obj: *const c_void,
+    ptr: *const AVOption,
+    _marker: PhantomData<&'a AVOption>,
+}
+
+impl<'a> OptionDescriptor<'a> {
+    pub fn as_ptr(&self) -> *const AVOption {
+        self.ptr
+    }
+
+    pub fn name(&self) -> &'a str {
+        unsafe { ptr_to_str((*self.ptr).name).unwrap_or("") }
+    }
+
+    pub fn help(&self) -> Option<&'a str> {
+        unsafe { ptr_to_str((*self.ptr).help) }
+    }
+
+    pub fn kind(&self) -> AVOptionType {
+        unsafe { (*self.ptr).type_ }
+    }
+
+    pub fn flags(&self) -> OptionFlags {
+        unsafe { OptionFlags::from_bits_truncate((*self.ptr).flags) }
+    }
+
+    pub fn min(&self) -> f64 {
+        unsafe { (*self.ptr).min }
+    }
+
+    pub fn max(&self) -> f64 {
+        unsafe { (*self.ptr).max }
+    }
+
+    /// The unit grouping this option with its named constants.
+    pub fn unit(&self) -> Option<&'a str> {
+        unsafe { ptr_to_str((*self.ptr).unit) }
+    }
+
+    pub fn default_value(&self) -> OptionDefault<'a> {
+        unsafe {
+            // default_val is a union, read whichever member the type uses
+            let value = &(*self.ptr).default_val as *const _;
+
+            match self.kind() {
+                AV_OPT_TYPE_DOUBLE | AV_OPT_TYPE_FLOAT => {
+                    OptionDefault::Double(*(value as *const f64))
+                }
+
+                AV_OPT_TYPE_RATIONAL => OptionDefault::Rational(*(value as *const AVRational)),
+
+                AV_OPT_TYPE_STRING | AV_OPT_TYPE_BINARY | AV_OPT_TYPE_DICT
+                | AV_OPT_TYPE_IMAGE_SIZE | AV_OPT_TYPE_VIDEO_RATE | AV_OPT_TYPE_COLOR => {
+                    OptionDefault::String(ptr_to_str(*(value as *const *const c_char)))
+                }
+
+                _ => OptionDefault::Int(*(value as *const i64)),
+            }
+        }
+    }
+
+    /// The named constants accepted by this option, e.g. the profiles of a
+    /// codec's `profile` option.
+    pub fn constants(&self) -> Vec<(&'a str, i64)> {
+        let unit = match self.unit() {
+            Some(unit) => unit,
+            None => return Vec::new(),
+        };
+
+        unsafe {
+            OptionIter::new(self.obj)
+                .filter(|opt| opt.kind() == AV_OPT_TYPE_CONST && opt.unit() == Some(unit))
+                .map(|opt| (opt.name(), *(&(*opt.ptr).default_val as *const _ as *const i64)))
+                .collect()
+        }
+    }
+}
+
+pub struct OptionIter<'a> {
+    obj: *const c_void,
+    cur: *const AVOption,
+    _marker: PhantomData<&'a AVOption>,
+}
+
+impl<'a> OptionIter<'a> {
+    unsafe fn new(obj: *const c_void) -> Self {
+        OptionIter {
+            obj,
+            cur: ptr::null(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Iterator for OptionIter<'a> {
+    type Item = OptionDescriptor<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.cur = av_opt_next(self.obj, self.cur);
+
+            if self.cur.is_null() {
+                None
+            } else {
+                Some(OptionDescriptor {
+                    obj: self.obj,
+                    ptr: self.cur,
+                    _marker: PhantomData,
+                })
+            }
+        }
+    }
+}
+
+/// Access to the `av_opt_*` API of an object.
+///
+/// Lookups also search child objects, such as a codec's private options.
+pub unsafe trait Options {
+    /// A pointer to a struct whose first member is a `const AVClass *`.
+    fn as_options_ptr(&self) -> *const c_void;
+
+    fn as_mut_options_ptr(&mut self) -> *mut c_void {
+        self.as_options_ptr() as *mut c_void
+    }
+
+    /// The options declared by the object's own class.
+    fn options(&self) -> OptionIter<'_> {
+        unsafe { OptionIter::new(self.as_options_ptr()) }
+    }
+
+    fn find_option(&self, name: &str) -> Option<OptionDescriptor<'_>> {
+        let name = cstring(name).ok()?;
+
+        unsafe {
+            // The option may belong to a child, which also holds its constants
+            let mut target = ptr::null_mut();
+            let opt = av_opt_find2(
+                self.as_options_ptr() as *mut c_void,
+                name.as_ptr(),
+                ptr::null(),
+                0,
+                AV_OPT_SEARCH_CHILDREN,
+                &mut target,
+            );
+
+            if opt.is_null() || target.is_null() {
+                None
+            } else {
+                Some(OptionDescriptor {
+                    obj: target,
+                    ptr: opt,
+                    _marker: PhantomData,
+                })
+            }
+        }
+    }
+
+    fn set_defaults(&mut self) {
+        unsafe { av_opt_set_defaults(self.as_mut_options_ptr()) }
+    }
+
+    /// Sets an option from its string representation, whatever its type.
+    fn set(&mut self, name: &str, value: &str) -> Result<(), AvError> {
+        let value = cstring(value)?;
+
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set(obj, name, value.as_ptr(), flags)
+        })
+    }
+
+    fn set_int(&mut self, name: &str, value: i64) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_int(obj, name, value, flags)
+        })
+    }
+
+    fn set_double(&mut self, name: &str, value: f64) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_double(obj, name, value, flags)
+        })
+    }
+
+    fn set_q(&mut self, name: &str, value: AVRational) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_q(obj, name, value, flags)
+        })
+    }
+
+    fn set_bin(&mut self, name: &str, value: &[u8]) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_bin(obj, name, value.as_ptr(), value.len() as c_int, flags)
+        })
+    }
+
+    fn set_image_size(&mut self, name: &str, width: i32, height: i32) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_image_size(obj, name, width, height, flags)
+        })
+    }
+
+    fn set_pixel_fmt(&mut self, name: &str, value: AVPixelFormat) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_pixel_fmt(obj, name, value, flags)
+        })
+    }
+
+    fn set_sample_fmt(&mut self, name: &str, value: SampleFormat) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_sample_fmt(obj, name, value.into(), flags)
+        })
+    }
+
+    fn set_video_rate(&mut self, name: &str, value: AVRational) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_video_rate(obj, name, value, flags)
+        })
+    }
+
+    fn set_channel_layout(&mut self, name: &str, value: ChannelLayout) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_channel_layout(obj, name, value.bits() as i64, flags)
+        })
+    }
+
+    fn set_dict(&mut self, name: &str, value: &Dictionary) -> Result<(), AvError> {
+        set(self.as_mut_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_set_dict_val(obj, name, value.as_ptr(), flags)
+        })
+    }
+
+    /// Applies every entry of `options`, returning the entries that did not
+    /// match any option.
+    fn set_from_dictionary(&mut self, options: &Dictionary) -> Result<Dictionary, AvError> {
+        let mut unused = options.clone();
+
+        unsafe {
+            AvError::check(av_opt_set_dict2(
+                self.as_mut_options_ptr(),
+                unused.as_mut_ptr(),
+                AV_OPT_SEARCH_CHILDREN,
+            ))?;
+        }
+
+        Ok(unused)
+    }
+
+    /// Gets an option formatted as a string, whatever its type.
+    fn get(&self, name: &str) -> Result<String, AvError> {
+        let mut value: *mut u8 = ptr::null_mut();
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get(obj, name, flags, &mut value)
+        })?;
+
+        unsafe {
+            let string = CStr::from_ptr(value as *const c_char)
+                .to_string_lossy()
+                .into_owned();
+            av_free(value as *mut c_void);
+
+            Ok(string)
+        }
+    }
+
+    fn get_int(&self, name: &str) -> Result<i64, AvError> {
+        let mut value = 0;
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get_int(obj, name, flags, &mut value)
+        }).map(|_| value)
+    }
+
+    fn get_double(&self, name: &str) -> Result<f64, AvError> {
+        let mut value = 0.0;
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get_double(obj, name, flags, &mut value)
+        }).map(|_| value)
+    }
+
+    fn get_q(&self, name: &str) -> Result<AVRational, AvError> {
+        let mut value = AVRational { num: 0, den: 1 };
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get_q(obj, name, flags, &mut value)
+        }).map(|_| value)
+    }
+
+    fn get_image_size(&self, name: &str) -> Result<(i32, i32), AvError> {
+        let (mut width, mut height) = (0, 0);
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get_image_size(obj, name, flags, &mut width, &mut height)
+        }).map(|_| (width, height))
+    }
+
+    fn get_pixel_fmt(&self, name: &str) -> Result<AVPixelFormat, AvError> {
+        let mut value = AVPixelFormat::AV_PIX_FMT_NONE;
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get_pixel_fmt(obj, name, flags, &mut value)
+        }).map(|_| value)
+    }
+
+    fn get_sample_fmt(&self, name: &str) -> Result<SampleFormat, AvError> {
+        let mut value = AVSampleFormat::AV_SAMPLE_FMT_NONE;
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get_sample_fmt(obj, name, flags, &mut value)
+        }).map(|_| SampleFormat(value))
+    }
+
+    fn get_video_rate(&self, name: &str) -> Result<AVRational, AvError> {
+        let mut value = AVRational { num: 0, den: 1 };
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get_video_rate(obj, name, flags, &mut value)
+        }).map(|_| value)
+    }
+
+    fn get_channel_layout(&self, name: &str) -> Result<ChannelLayout, AvError> {
+        let mut value = 0;
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get_channel_layout(obj, name, flags, &mut value)
+        }).map(|_| ChannelLayout::from_bits_truncate(value as u64))
+    }
+
+    fn get_dict(&self, name: &str) -> Result<Dictionary, AvError> {
+        let mut value = ptr::null_mut();
+
+        get(self.as_options_ptr(), name, |obj, name, flags| unsafe {
+            av_opt_get_dict_val(obj, name, flags, &mut value)
+        }).map(|_| unsafe { Dictionary::from_raw(value) })
+    }
+}
+
+fn set<F>(obj: *mut c_void, name: &str, f: F) -> Result<(), AvError>
+where
+    F: FnOnce(*mut c_void, *const c_char, c_int) -> c_int,
+{
+    let name = cstring(name)?;
+    AvError::check(f(obj, name.as_ptr(), AV_OPT_SEARCH_CHILDREN)).map(|_| ())
+}
+
+// the getters take a `void *` but leave the object untouched
+fn get<F>(obj: *const c_void, name: &str, f: F) -> Result<(), AvError>
+where
+    F: FnOnce(*mut c_void, *const c_char, c_int) -> c_int,
+{
+    set(obj as *mut c_void, name, f)
+}
+
+macro_rules! impl_options {
+	($($(#[$attr:meta])* $ty:ident),*) => ($(
+		$(#[$attr])*
+		unsafe impl Options for ::$ty {
+			fn as_options_ptr(&self) -> *const c_void {
+				self as *const ::$ty as *const c_void
+			}
+		}
+	)*)
+}
+
+impl_options!(
+    #[cfg(feature = "avcodec")]
+    AVCodecContext,
+    #[cfg(feature = "avcodec")]
+    AVBSFContext,
+    #[cfg(feature = "avformat")]
+    AVFormatContext,
+    #[cfg(feature = "avformat")]
+    AVIOContext,
+    #[cfg(feature = "avfilter")]
+    AVFilterContext,
+    #[cfg(feature = "avfilter")]
+    AVFilterGraph,
+    #[cfg(feature = "avresample")]
+    AVAudioResampleContext,
+    #[cfg(feature = "swresample")]
+    SwrContext,
+    #[cfg(feature = "swscale")]
+    SwsContext
+);
+
+#[cfg(all(test, feature = "avcodec"))]
+mod tests {
+    use super::*;
+    use AVSampleFormat::AV_SAMPLE_FMT_FLTP;
+    use {Codec, EncoderBuilder};
+
+    #[test]
+    fn constants_of_own_and_private_options() {
+        let encoder = EncoderBuilder::new(Codec::find_encoder_by_name("aac").unwrap())
+            .sample_rate(48000)
+            .sample_format(SampleFormat(AV_SAMPLE_FMT_FLTP))
+            .channel_layout(ChannelLayout::STEREO)
+            .open()
+            .unwrap();
+
+        // Declared by `AVCodecContext`
+        let flags = encoder.find_option("flags").unwrap().constants();
+        assert!(flags.iter().any(|&(name, _)| name == "global_header"));
+
+        // Declared by the private class of the encoder
+        let coder = encoder.find_option("aac_coder").unwrap().constants();
+        let names = coder.iter().map(|&(name, _)| name).collect::<Vec<_>>();
+        assert!(names.contains(&"twoloop") && names.contains(&"fast"), "{:?}", names);
+    }
+}
@@ -0,0 +1,129 @@
+use std::ptr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use libc::{c_int, EINVAL, ERANGE};
+use AVPixelFormat::AV_PIX_FMT_NONE;
+use {av_get_pix_fmt, av_parse_color, av_parse_ratio, av_parse_time, av_parse_video_rate,
+     av_parse_video_size, cstring, AVPixelFormat, AVRational, AvError, AVERROR};
+
+/// Parses a `WIDTHxHEIGHT` size or an abbreviation such as `"hd720"`.
+pub fn parse_video_size(value: &str) -> Result<(u32, u32), AvError> {
+    let value = cstring(value)?;
+    let (mut width, mut height) = (0, 0);
+
+    unsafe { AvError::check(av_parse_video_size(&mut width, &mut height, value.as_ptr()))? };
+
+    Ok((width as u32, height as u32))
+}
+
+/// Parses a frame rate given as a ratio, a decimal number or an
+/// abbreviation such as `"ntsc"`.
+pub fn parse_video_rate(value: &str) -> Result<AVRational, AvError> {
+    let value = cstring(value)?;
+    let mut rate = AVRational { num: 0, den: 0 };
+
+    unsafe { AvError::check(av_parse_video_rate(&mut rate, value.as_ptr()))? };
+
+    Ok(rate)
+}
+
+/// Parses a ratio such as `"16:9"`, `"4/3"` or `"1.85"`, or an expression,
+/// with numerator and denominator at most `max`.
+pub fn parse_ratio(value: &str, max: i32) -> Result<AVRational, AvError> {
+    let value = cstring(value)?;
+    let mut ratio = AVRational { num: 0, den: 0 };
+
+    unsafe {
+        AvError::check(av_parse_ratio(&mut ratio, value.as_ptr(), max, 0, ptr::null_mut()))?
+    };
+
+    Ok(ratio)
+}
+
+/// Parses a color name or `0xRRGGBB[AA]` / `#RRGGBB[AA]` value, optionally
+/// followed by `@alpha`, into RGBA.
+pub fn parse_color(value: &str) -> Result<[u8; 4], AvError> {
+    let value = cstring(value)?;
+    let mut rgba = [0; 4];
+
+    unsafe {
+        AvError::check(av_parse_color(rgba.as_mut_ptr(), value.as_ptr(), -1, ptr::null_mut()))?
+    };
+
+    Ok(rgba)
+}
+
+/// Parses a duration such as `"01:02:03.5"`, `"90.5"` or `"200ms"` when
+/// `duration` is set, or otherwise a date such as `"2000-01-01 12:00:00Z"`
+/// or `"now"`, as time since the Unix epoch.
+///
+/// Negative durations and dates before the epoch fail with `ERANGE`, use
+/// `parse_time_micros` to accept them.
+pub fn parse_time(value: &str, duration: bool) -> Result<Duration, AvError> {
+    let micros = parse_time_micros(value, duration)?;
+
+    if micros < 0 {
+        Err(AvError(AVERROR(ERANGE)))
+    } else {
+        Ok(Duration::new(
+            (micros / 1_000_000) as u64,
+            (micros % 1_000_000) as u32 * 1_000,
+        ))
+    }
+}
+
+/// Like `parse_time`, in microseconds (`AV_TIME_BASE` units), negative for
+/// durations such as `"-1.5"` and dates before the epoch.
+pub fn parse_time_micros(value: &str, duration: bool) -> Result<i64, AvError> {
+    let value = cstring(value)?;
+    let mut micros = 0;
+
+    unsafe { AvError::check(av_parse_time(&mut micros, value.as_ptr(), duration as c_int))? };
+
+    Ok(micros)
+}
+
+impl FromStr for AVPixelFormat {
+    type Err = AvError;
+
+    /// Looks up a pixel format by its FFmpeg name, e.g. `"yuv420p"`.
+    fn from_str(value: &str) -> Result<Self, AvError> {
+        let value = cstring(value)?;
+
+        match unsafe { av_get_pix_fmt(value.as_ptr()) } {
+            AV_PIX_FMT_NONE => Err(AvError(AVERROR(EINVAL))),
+            format => Ok(format),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_time("01:02:03.5", true), Ok(Duration::from_millis(3_723_500)));
+        assert_eq!(parse_time("200ms", true), Ok(Duration::from_millis(200)));
+        assert_eq!(parse_time("-1.5", true), Err(AvError(AVERROR(ERANGE))));
+        assert!(parse_time("soon", true).is_err());
+    }
+
+    #[test]
+    fn parse_signed_durations() {
+        assert_eq!(parse_time_micros("01:02:03.5", true), Ok(3_723_500_000));
+        assert_eq!(parse_time_micros("-1.5", true), Ok(-1_500_000));
+        assert_eq!(parse_time_micros("-00:01", true), Ok(-1_000_000));
+    }
+
+    #[test]
+    fn parse_dates() {
+        assert_eq!(parse_time("1970-01-01 00:00:00Z", false), Ok(Duration::from_secs(0)));
+        assert_eq!(
+            parse_time("2000-01-01 12:00:00Z", false),
+            Ok(Duration::from_secs(946_728_000))
+        );
+        assert_eq!(parse_time_micros("1969-12-31 23:59:59Z", false), Ok(-1_000_000));
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(feature = "rand_core")]
+use rand_core::{self, impls, RngCore};
+
+use libc::c_uint;
+use {av_bmg_get, av_get_random_seed, av_lfg_init, AVLFG};
+
+/// A 32-bit seed from the best source of randomness available, e.g.
+/// `/dev/urandom`, falling back to timer jitter.
+pub fn random_seed() -> u32 {
+    unsafe { av_get_random_seed() }
+}
+
+/// FFmpeg's lagged Fibonacci generator, as used for dithering and noise.
+///
+/// Not cryptographically secure. With the `rand_core` feature it implements
+/// `RngCore`, producing the same sequence as FFmpeg for the same seed.
+#[derive(Copy, Clone)]
+pub struct Lfg {
+    lfg: AVLFG,
+}
+
+impl Lfg {
+    pub fn new(seed: u32) -> Self {
+        let mut lfg = unsafe { ::std::mem::zeroed() };
+        unsafe { av_lfg_init(&mut lfg, seed as c_uint) };
+
+        Lfg { lfg }
+    }
+
+    /// Seeded with `random_seed`.
+    pub fn from_random_seed() -> Self {
+        Lfg::new(random_seed())
+    }
+
+    pub fn as_ptr(&self) -> *const AVLFG {
+        &self.lfg
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVLFG {
+        &mut self.lfg
+    }
+
+    // `av_lfg_get` and `av_mlfg_get` are inline, so bindgen skips them
+
+    /// The next value of the additive generator, `av_lfg_get`.
+    pub fn get(&mut self) -> u32 {
+        let value = self.state(-24).wrapping_add(self.state(-55));
+        self.advance(value)
+    }
+
+    /// The next value of the multiplicative generator, `av_mlfg_get`, of
+    /// higher quality but slower.
+    pub fn get_mlfg(&mut self) -> u32 {
+        let (a, b) = (self.state(-55), self.state(-24));
+        let value = a.wrapping_mul(b).wrapping_mul(2).wrapping_add(a).wrapping_add(b);
+        self.advance(value)
+    }
+
+    /// Two normally distributed values with a mean of 0 and a standard
+    /// deviation of 1, using the Box-Muller transform.
+    pub fn get_gaussian(&mut self) -> (f64, f64) {
+        let mut out = [0.0; 2];
+        unsafe { av_bmg_get(&mut self.lfg, out.as_mut_ptr()) };
+
+        (out[0], out[1])
+    }
+
+    fn state(&self, offset: i32) -> u32 {
+        self.lfg.state[(self.lfg.index.wrapping_add(offset) & 63) as usize] as u32
+    }
+
+    fn advance(&mut self, value: u32) -> u32 {
+        self.lfg.state[(self.lfg.index & 63) as usize] = value as c_uint;
+        self.lfg.index = self.lfg.index.wrapping_add(1);
+
+        value
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl RngCore for Lfg {
+    fn next_u32(&mut self) -> u32 {
+        self.get()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use std::ffi::CString;
+use std::ptr;
+use std::str::FromStr;
+
+use libc::{c_int, EINVAL};
+use AVSampleFormat::*;
+use {av_get_bytes_per_sample, av_get_packed_sample_fmt, av_get_planar_sample_fmt,
+     av_get_sample_fmt, av_get_sample_fmt_name, av_sample_fmt_is_planar,
+     av_samples_get_buffer_size, ptr_to_str, AVSampleFormat, AvError, AVERROR};
+
+/// An audio sample format, packed (interleaved) or planar.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SampleFormat(pub AVSampleFormat);
+
+impl SampleFormat {
+    /// Looks up a sample format by its FFmpeg name, e.g. `"s16"` or `"fltp"`.
+    pub fn from_name(name: &str) -> Option<SampleFormat> {
+        let name = CString::new(name).ok()?;
+
+        match unsafe { av_get_sample_fmt(name.as_ptr()) } {
+            AV_SAMPLE_FMT_NONE => None,
+            format => Some(SampleFormat(format)),
+        }
+    }
+
+    pub fn name(self) -> Option<&'static str> {
+        unsafe { ptr_to_str(av_get_sample_fmt_name(self.0)) }
+    }
+
+    pub fn bytes_per_sample(self) -> usize {
+        unsafe { av_get_bytes_per_sample(self.0) as usize }
+    }
+
+    pub fn is_planar(self) -> bool {
+        unsafe { av_sample_fmt_is_planar(self.0) == 1 }
+    }
+
+    pub fn is_packed(self) -> bool {
+        !self.is_planar()
+    }
+
+    /// The interleaved variant of this format, or itself if already packed.
+    pub fn packed(self) -> SampleFormat {
+        SampleFormat(unsafe { av_get_packed_sample_fmt(self.0) })
+    }
+
+    /// The planar variant of this format, or itself if already planar.
+    pub fn planar(self) -> SampleFormat {
+        SampleFormat(unsafe { av_get_planar_sample_fmt(self.0) })
+    }
+
+    /// Size in bytes of a buffer holding `samples` samples for `channels`
+    /// channels, with each plane aligned to `align` (0 for the default).
+    pub fn buffer_size(self, channels: usize, samples: usize, align: usize) -> Option<usize> {
+        let size = unsafe {
+            av_samples_get_buffer_size(
+                ptr::null_mut(),
+                channels as c_int,
+                samples as c_int,
+                self.0,
+                align as c_int,
+            )
+        };
+
+        if size < 0 {
+            None
+        } else {
+            Some(size as usize)
+        }
+    }
+}
+
+impl From<AVSampleFormat> for SampleFormat {
+    fn from(value: AVSampleFormat) -> SampleFormat {
+        SampleFormat(value)
+    }
+}
+
+impl From<SampleFormat> for AVSampleFormat {
+    fn from(value: SampleFormat) -> AVSampleFormat {
+        value.0
+    }
+}
+
+impl FromStr for SampleFormat {
+    type Err = AvError;
+
+    fn from_str(value: &str) -> Result<Self, AvError> {
+        SampleFormat::from_name(value).ok_or(AvError(AVERROR(EINVAL)))
+    }
+}
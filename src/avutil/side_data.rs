@@ -0,0 +1,334 @@
+use std::marker::PhantomData;
+use std::{mem, ptr, slice};
+
+use libc::{c_int, EINVAL, ENOMEM};
+use AVDownmixType::AV_DOWNMIX_TYPE_NB;
+use AVFrameSideDataType::*;
+use AVStereo3DType::AV_STEREO3D_COLUMNS;
+use AVStereo3DView::AV_STEREO3D_VIEW_RIGHT;
+use {av_display_matrix_flip, av_display_rotation_get, av_display_rotation_set,
+     av_frame_get_side_data, av_frame_new_side_data, av_frame_new_side_data_from_buf,
+     av_frame_remove_side_data, av_frame_side_data_name, ptr_to_str, AVDownmixInfo,
+     AVFrameSideData, AVFrameSideDataType, AVMotionVector, AVReplayGain, AVStereo3D, AvError,
+     Buffer, DictionaryRef, Frame, AVERROR};
+
+/// A 3x3 transformation matrix in 16.16 fixed point (2.30 for the last
+/// column), mapping decoded pixels to their display position.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DisplayMatrix(pub [i32; 9]);
+
+impl DisplayMatrix {
+    /// A pure counterclockwise rotation by `angle` degrees.
+    pub fn from_rotation(angle: f64) -> DisplayMatrix {
+        let mut matrix = DisplayMatrix([0; 9]);
+        unsafe { av_display_rotation_set(matrix.0.as_mut_ptr(), angle) }
+
+        matrix
+    }
+
+    /// The counterclockwise rotation in degrees, within [-180, 180], or
+    /// `None` if the matrix is singular.
+    pub fn rotation(&self) -> Option<f64> {
+        let angle = unsafe { av_display_rotation_get(self.0.as_ptr()) };
+
+        if angle.is_nan() {
+            None
+        } else {
+            Some(angle)
+        }
+    }
+
+    pub fn flip(&mut self, horizontal: bool, vertical: bool) {
+        unsafe {
+            av_display_matrix_flip(self.0.as_mut_ptr(), horizontal as c_int, vertical as c_int)
+        }
+    }
+}
+
+/// Side data decoded according to its type.
+#[derive(Copy, Clone, Debug)]
+pub enum SideDataPayload<'a> {
+    DisplayMatrix(DisplayMatrix),
+    MotionVectors(&'a [AVMotionVector]),
+    Stereo3D(AVStereo3D),
+    ReplayGain(AVReplayGain),
+    DownmixInfo(AVDownmixInfo),
+    /// Types without a typed decoder, or truncated, misaligned or invalid
+    /// payloads.
+    Raw(&'a [u8]),
+}
+
+/// Side data attached to a frame.
+#[derive(Copy, Clone)]
+pub struct SideData<'a> {
+    ptr: *const AVFrameSideData,
+    _marker: PhantomData<&'a Frame>,
+}
+
+impl<'a> SideData<'a> {
+    pub unsafe fn wrap(ptr: *const AVFrameSideData) -> Self {
+        SideData {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const AVFrameSideData {
+        self.ptr
+    }
+
+    pub fn kind(&self) -> AVFrameSideDataType {
+        unsafe { (*self.ptr).type_ }
+    }
+
+    pub fn name(&self) -> Option<&'static str> {
+        unsafe { ptr_to_str(av_frame_side_data_name(self.kind())) }
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        unsafe {
+            if (*self.ptr).size <= 0 {
+                &[]
+            } else {
+                slice::from_raw_parts((*self.ptr).data, (*self.ptr).size as usize)
+            }
+        }
+    }
+
+    pub fn metadata(&self) -> DictionaryRef<'a> {
+        unsafe { DictionaryRef::wrap((*self.ptr).metadata) }
+    }
+
+    pub fn payload(&self) -> SideDataPayload<'a> {
+        let data = self.data();
+
+        unsafe {
+            match self.kind() {
+                AV_FRAME_DATA_DISPLAYMATRIX if data.len() >= mem::size_of::<DisplayMatrix>() => {
+                    SideDataPayload::DisplayMatrix(ptr::read_unaligned(data.as_ptr() as *const _))
+                }
+                AV_FRAME_DATA_MOTION_VECTORS => match cast_slice(data) {
+                    Some(vectors) => SideDataPayload::MotionVectors(vectors),
+                    None => SideDataPayload::Raw(data),
+                },
+                AV_FRAME_DATA_STEREO3D => match stereo3d(data) {
+                    Some(stereo3d) => SideDataPayload::Stereo3D(stereo3d),
+                    None => SideDataPayload::Raw(data),
+                },
+                AV_FRAME_DATA_REPLAYGAIN if data.len() >= mem::size_of::<AVReplayGain>() => {
+                    SideDataPayload::ReplayGain(ptr::read_unaligned(data.as_ptr() as *const _))
+                }
+                AV_FRAME_DATA_DOWNMIX_INFO => match downmix_info(data) {
+                    Some(info) => SideDataPayload::DownmixInfo(info),
+                    None => SideDataPayload::Raw(data),
+                },
+                _ => SideDataPayload::Raw(data),
+            }
+        }
+    }
+}
+
+impl<'a> ::std::fmt::Debug for SideData<'a> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("SideData")
+            .field("kind", &self.kind())
+            .field("payload", &self.payload())
+            .finish()
+    }
+}
+
+// Side data can be any bytes attached through `add_side_data`, while the
+// enum fields of these structs are only valid for their declared values
+
+fn stereo3d(data: &[u8]) -> Option<AVStereo3D> {
+    if data.len() < mem::size_of::<AVStereo3D>() {
+        return None;
+    }
+
+    unsafe {
+        let base = mem::MaybeUninit::<AVStereo3D>::uninit();
+        let base = base.as_ptr();
+        let (kind, view) = (ptr::addr_of!((*base).type_), ptr::addr_of!((*base).view));
+
+        if enum_in_range(data, base, kind, AV_STEREO3D_COLUMNS as c_int)
+            && enum_in_range(data, base, view, AV_STEREO3D_VIEW_RIGHT as c_int)
+        {
+            Some(ptr::read_unaligned(data.as_ptr() as *const AVStereo3D))
+        } else {
+            None
+        }
+    }
+}
+
+fn downmix_info(data: &[u8]) -> Option<AVDownmixInfo> {
+    if data.len() < mem::size_of::<AVDownmixInfo>() {
+        return None;
+    }
+
+    unsafe {
+        let base = mem::MaybeUninit::<AVDownmixInfo>::uninit();
+        let base = base.as_ptr();
+        let kind = ptr::addr_of!((*base).preferred_downmix_type);
+
+        if enum_in_range(data, base, kind, AV_DOWNMIX_TYPE_NB as c_int - 1) {
+            Some(ptr::read_unaligned(data.as_ptr() as *const AVDownmixInfo))
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether the `int` sized enum `field` of the `T` at the start of `data`
+/// is within `0..=max`.
+unsafe fn enum_in_range<T, E>(data: &[u8], base: *const T, field: *const E, max: c_int) -> bool {
+    let offset = field as usize - base as usize;
+    let value = ptr::read_unaligned(data.as_ptr().add(offset) as *const c_int);
+
+    value >= 0 && value <= max
+}
+
+// Buffers attached from Rust may be less aligned than FFmpeg's allocations
+unsafe fn cast_slice<T>(data: &[u8]) -> Option<&[T]> {
+    if data.is_empty() {
+        Some(&[])
+    } else if data.as_ptr() as usize % mem::align_of::<T>() != 0 {
+        None
+    } else {
+        Some(slice::from_raw_parts(data.as_ptr() as *const T, data.len() / mem::size_of::<T>()))
+    }
+}
+
+/// Iterator over the side data of a frame, in insertion order.
+pub struct SideDataIter<'a> {
+    frame: &'a Frame,
+    index: usize,
+}
+
+impl<'a> Iterator for SideDataIter<'a> {
+    type Item = SideData<'a>;
+
+    fn next(&mut self) -> Option<SideData<'a>> {
+        unsafe {
+            let frame = self.frame.as_ptr();
+
+            if self.index >= (*frame).nb_side_data as usize {
+                return None;
+            }
+
+            let item = SideData::wrap(*(*frame).side_data.add(self.index));
+            self.index += 1;
+
+            Some(item)
+        }
+    }
+}
+
+impl Frame {
+    pub fn side_data(&self) -> SideDataIter<'_> {
+        SideDataIter {
+            frame: self,
+            index: 0,
+        }
+    }
+
+    /// The first side data of the given type.
+    pub fn side_data_of(&self, kind: AVFrameSideDataType) -> Option<SideData<'_>> {
+        unsafe {
+            let ptr = av_frame_get_side_data(self.as_ptr(), kind);
+
+            if ptr.is_null() {
+                None
+            } else {
+                Some(SideData::wrap(ptr))
+            }
+        }
+    }
+
+    /// Attaches a copy of `data`.
+    pub fn add_side_data(&mut self, kind: AVFrameSideDataType, data: &[u8]) -> Result<(), AvError> {
+        if data.len() > c_int::max_value() as usize {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        unsafe {
+            let side_data = av_frame_new_side_data(self.as_mut_ptr(), kind, data.len() as c_int);
+            if side_data.is_null() {
+                return Err(AvError(AVERROR(ENOMEM)));
+            }
+            ptr::copy_nonoverlapping(data.as_ptr(), (*side_data).data, data.len());
+        }
+
+        Ok(())
+    }
+
+    /// Attaches `buffer` without copying it.
+    pub fn add_side_data_buffer(
+        &mut self,
+        kind: AVFrameSideDataType,
+        buffer: Buffer,
+    ) -> Result<(), AvError> {
+        unsafe {
+            let buf = buffer.into_raw();
+            let side_data = av_frame_new_side_data_from_buf(self.as_mut_ptr(), kind, buf);
+
+            if side_data.is_null() {
+                drop(Buffer::from_raw(buf));
+                Err(AvError(AVERROR(ENOMEM)))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    pub fn set_display_matrix(&mut self, matrix: &DisplayMatrix) -> Result<(), AvError> {
+        let data = unsafe {
+            slice::from_raw_parts(matrix.0.as_ptr() as *const u8, mem::size_of::<DisplayMatrix>())
+        };
+
+        self.remove_side_data(AV_FRAME_DATA_DISPLAYMATRIX);
+        self.add_side_data(AV_FRAME_DATA_DISPLAYMATRIX, data)
+    }
+
+    /// Removes every side data of the given type.
+    pub fn remove_side_data(&mut self, kind: AVFrameSideDataType) {
+        unsafe { av_frame_remove_side_data(self.as_mut_ptr(), kind) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Exposes its bytes from an odd address
+    struct Misaligned(Vec<u64>);
+
+    impl AsMut<[u8]> for Misaligned {
+        fn as_mut(&mut self) -> &mut [u8] {
+            let len = self.0.len() * 8;
+            unsafe { &mut slice::from_raw_parts_mut(self.0.as_mut_ptr() as *mut u8, len)[1..] }
+        }
+    }
+
+    #[test]
+    fn misaligned_payloads() {
+        let size = mem::size_of::<AVMotionVector>() * 2;
+        let mut frame = Frame::new();
+
+        let buffer = Buffer::from_owned(Misaligned(vec![0; size / 8 + 1])).unwrap();
+        frame.add_side_data_buffer(AV_FRAME_DATA_MOTION_VECTORS, buffer).unwrap();
+        let buffer = Buffer::from_owned(Misaligned(vec![0x0101_0101_0101_0101; 3])).unwrap();
+        frame.add_side_data_buffer(AV_FRAME_DATA_REPLAYGAIN, buffer).unwrap();
+
+        let vectors = frame.side_data_of(AV_FRAME_DATA_MOTION_VECTORS).unwrap();
+        match vectors.payload() {
+            SideDataPayload::Raw(data) => assert!(data.len() > size),
+            payload => panic!("{:?}", payload),
+        }
+
+        let gain = frame.side_data_of(AV_FRAME_DATA_REPLAYGAIN).unwrap();
+        match gain.payload() {
+            SideDataPayload::ReplayGain(gain) => assert_eq!(gain.track_peak, 0x0101_0101),
+            payload => panic!("{:?}", payload),
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use libc::{c_int, c_uint, c_void, EINVAL, ENOMEM};
+use AVThreadMessageFlags::AV_THREAD_MESSAGE_NONBLOCK;
+use {av_thread_message_flush, av_thread_message_queue_alloc, av_thread_message_queue_free,
+     av_thread_message_queue_nb_elems, av_thread_message_queue_recv,
+     av_thread_message_queue_send, av_thread_message_queue_set_err_recv,
+     av_thread_message_queue_set_err_send, av_thread_message_queue_set_free_func,
+     AVThreadMessageQueue, AvError, AVERROR};
+
+// Messages are copied into the queue byte for byte, so the trailing byte
+// keeps zero-sized types from making a zero-sized element.
+#[repr(C)]
+struct Slot<T>(T, u8);
+
+unsafe extern "C" fn free_message<T>(msg: *mut c_void) {
+    // Panics must not unwind into C, a panicking drop leaks instead
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        drop(ptr::read_unaligned(msg as *const Slot<T>))
+    }));
+}
+
+/// A message that could not be sent, handed back along with the error.
+pub struct SendError<T> {
+    pub error: AvError,
+    pub message: T,
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SendError").field("error", &self.error).finish()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl<T> error::Error for SendError<T> {
+    fn description(&self) -> &str {
+        "failed to send message"
+    }
+}
+
+impl<T> From<SendError<T>> for AvError {
+    fn from(value: SendError<T>) -> AvError {
+        value.error
+    }
+}
+
+/// A bounded multi-producer multi-consumer queue of Rust values, backed by
+/// `AVThreadMessageQueue`.
+///
+/// Shared between threads by reference, e.g. in an `Arc`. Values still
+/// queued when the queue is flushed or dropped are dropped.
+pub struct ThreadMessageQueue<T: Send> {
+    ptr: *mut AVThreadMessageQueue,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for ThreadMessageQueue<T> {}
+unsafe impl<T: Send> Sync for ThreadMessageQueue<T> {}
+
+impl<T: Send> ThreadMessageQueue<T> {
+    /// A queue holding at most `capacity` messages.
+    ///
+    /// Fails with `ENOSYS` if FFmpeg was built without threads.
+    pub fn new(capacity: usize) -> Result<Self, AvError> {
+        let size = mem::size_of::<Slot<T>>();
+
+        if capacity == 0 || capacity > c_int::max_value() as usize / size {
+            return Err(AvError(AVERROR(EINVAL)));
+        }
+
+        let mut ptr = ptr::null_mut();
+
+        unsafe {
+            AvError::check(av_thread_message_queue_alloc(
+                &mut ptr,
+                capacity as c_uint,
+                size as c_uint,
+            ))?;
+        }
+
+        if ptr.is_null() {
+            return Err(AvError(AVERROR(ENOMEM)));
+        }
+
+        unsafe { av_thread_message_queue_set_free_func(ptr, Some(free_message::<T>)) };
+
+        Ok(ThreadMessageQueue {
+            ptr,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn as_mut_ptr(&self) -> *mut AVThreadMessageQueue {
+        self.ptr
+    }
+
+    /// Sends a message, waiting for room if the queue is full.
+    ///
+    /// Fails with the error set by `set_err_send`.
+    pub fn send(&self, message: T) -> Result<(), SendError<T>> {
+        self.send_with_flags(message, 0)
+    }
+
+    /// Sends a message, failing with `EAGAIN` if the queue is full.
+    pub fn try_send(&self, message: T) -> Result<(), SendError<T>> {
+        self.send_with_flags(message, AV_THREAD_MESSAGE_NONBLOCK as c_uint)
+    }
+
+    fn send_with_flags(&self, message: T, flags: c_uint) -> Result<(), SendError<T>> {
+        let mut slot = ManuallyDrop::new(Slot(message, 0));
+
+        let ret = unsafe {
+            av_thread_message_queue_send(
+                self.ptr,
+                &mut *slot as *mut Slot<T> as *mut c_void,
+                flags,
+            )
+        };
+
+        // On success the queue owns the bytes and `slot` must not be dropped
+        if ret < 0 {
+            Err(SendError {
+                error: AvError(ret),
+                message: ManuallyDrop::into_inner(slot).0,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Receives a message, waiting for one if the queue is empty.
+    ///
+    /// Fails with the error set by `set_err_recv` once the queue is empty.
+    pub fn recv(&self) -> Result<T, AvError> {
+        self.recv_with_flags(0)
+    }
+
+    /// Receives a message, failing with `EAGAIN` if the queue is empty.
+    pub fn try_recv(&self) -> Result<T, AvError> {
+        self.recv_with_flags(AV_THREAD_MESSAGE_NONBLOCK as c_uint)
+    }
+
+    fn recv_with_flags(&self, flags: c_uint) -> Result<T, AvError> {
+        let mut slot = MaybeUninit::<Slot<T>>::uninit();
+
+        unsafe {
+            AvError::check(av_thread_message_queue_recv(
+                self.ptr,
+                slot.as_mut_ptr() as *mut c_void,
+                flags,
+            ))?;
+
+            Ok(slot.assume_init().0)
+        }
+    }
+
+    /// Makes senders fail with `error` from now on, e.g. `AVERROR_EOF` once
+    /// the consumer has stopped. Blocked senders are woken up.
+    pub fn set_err_send(&self, error: AvError) {
+        unsafe { av_thread_message_queue_set_err_send(self.ptr, error.0) }
+    }
+
+    /// Makes receivers fail with `error` once the queue is empty, e.g.
+    /// `AVERROR_EOF` when producers are done. Blocked receivers are woken up.
+    pub fn set_err_recv(&self, error: AvError) {
+        unsafe { av_thread_message_queue_set_err_recv(self.ptr, error.0) }
+    }
+
+    /// Number of messages queued.
+    pub fn len(&self) -> usize {
+        unsafe { av_thread_message_queue_nb_elems(self.ptr).max(0) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops all queued messages, waking up blocked senders.
+    pub fn flush(&self) {
+        unsafe { av_thread_message_flush(self.ptr) }
+    }
+}
+
+impl<T: Send> Drop for ThreadMessageQueue<T> {
+    fn drop(&mut self) {
+        unsafe { av_thread_message_queue_free(&mut self.ptr) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use libc::EAGAIN;
+
+    use super::*;
+    use AVERROR_EOF;
+
+    #[test]
+    fn blocking_send_and_recv() {
+        // A single slot, so the producer blocks on every message
+        let queue = Arc::new(ThreadMessageQueue::new(1).unwrap());
+
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for index in 0..100 {
+                    queue.send(format!("message {}", index)).unwrap();
+                }
+                queue.set_err_recv(AvError(AVERROR_EOF));
+            })
+        };
+
+        for index in 0..100 {
+            assert_eq!(queue.recv().unwrap(), format!("message {}", index));
+        }
+        assert_eq!(queue.recv().unwrap_err(), AvError(AVERROR_EOF));
+
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn set_err_recv_wakes_receiver() {
+        let queue = Arc::new(ThreadMessageQueue::<u32>::new(4).unwrap());
+
+        let consumer = {
+            let queue = queue.clone();
+            thread::spawn(move || queue.recv())
+        };
+
+        thread::sleep(Duration::from_millis(50));
+        queue.set_err_recv(AvError(AVERROR_EOF));
+
+        assert_eq!(consumer.join().unwrap(), Err(AvError(AVERROR_EOF)));
+    }
+
+    #[test]
+    fn flush_drops_messages() {
+        let value = Arc::new(());
+        let queue = Arc::new(ThreadMessageQueue::new(2).unwrap());
+
+        queue.send(value.clone()).unwrap();
+        queue.send(value.clone()).unwrap();
+        assert_eq!(queue.len(), 2);
+
+        let sender = {
+            let (queue, value) = (queue.clone(), value.clone());
+            thread::spawn(move || queue.send(value).map_err(|err| err.error))
+        };
+
+        // Flushing makes room for the blocked sender
+        thread::sleep(Duration::from_millis(50));
+        queue.flush();
+        sender.join().unwrap().unwrap();
+
+        assert_eq!(queue.len(), 1);
+        assert_eq!(Arc::strong_count(&value), 2);
+
+        queue.flush();
+        assert!(queue.is_empty());
+        assert_eq!(Arc::strong_count(&value), 1);
+        assert_eq!(queue.try_recv().unwrap_err(), AvError(AVERROR(EAGAIN)));
+    }
+}
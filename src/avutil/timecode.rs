@@ -0,0 +1,200 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::ptr;
+
+use libc::{c_char, c_int};
+use AVTimecodeFlag::*;
+use {av_timecode_adjust_ntsc_framenum2, av_timecode_check_frame_rate,
+     av_timecode_get_smpte_from_framenum, av_timecode_init, av_timecode_init_from_string,
+     av_timecode_make_mpeg_tc_string, av_timecode_make_string, cstring, AVRational, AVTimecode,
+     AvError, DictionaryRef, AV_TIMECODE_STR_SIZE};
+
+bitflags! {
+    pub struct TimecodeFlags: u32 {
+        const DROP_FRAME     = AV_TIMECODE_FLAG_DROPFRAME as u32;
+        /// Wrap around after 24 hours.
+        const MAX_24_HOURS   = AV_TIMECODE_FLAG_24HOURSMAX as u32;
+        const ALLOW_NEGATIVE = AV_TIMECODE_FLAG_ALLOWNEGATIVE as u32;
+    }
+}
+
+/// A SMPTE timecode: a starting frame number and a frame rate, formatted as
+/// `hh:mm:ss:ff`, or `hh:mm:ss;ff` with drop-frame counting.
+#[derive(Copy, Clone, Debug)]
+pub struct Timecode {
+    tc: AVTimecode,
+}
+
+impl Timecode {
+    /// Drop-frame counting is only supported at 30000/1001 and 60000/1001.
+    pub fn new(rate: AVRational, flags: TimecodeFlags, start: i32) -> Result<Self, AvError> {
+        let mut tc = unsafe { ::std::mem::zeroed() };
+
+        unsafe {
+            AvError::check(av_timecode_init(
+                &mut tc,
+                rate,
+                flags.bits() as c_int,
+                start,
+                ptr::null_mut(),
+            ))?
+        };
+
+        Ok(Timecode { tc })
+    }
+
+    /// Parses a `hh:mm:ss:ff` timecode as the starting frame, with a `;` or
+    /// `.` before the frames selecting drop-frame counting.
+    pub fn from_string(rate: AVRational, value: &str) -> Result<Self, AvError> {
+        let value = cstring(value)?;
+        let mut tc = unsafe { ::std::mem::zeroed() };
+
+        unsafe {
+            AvError::check(av_timecode_init_from_string(
+                &mut tc,
+                rate,
+                value.as_ptr(),
+                ptr::null_mut(),
+            ))?
+        };
+
+        Ok(Timecode { tc })
+    }
+
+    /// The `timecode` entry of stream or frame metadata, if present and
+    /// valid at `rate`.
+    pub fn from_metadata(metadata: DictionaryRef<'_>, rate: AVRational) -> Option<Self> {
+        metadata
+            .get("timecode")
+            .and_then(|value| Timecode::from_string(rate, value).ok())
+    }
+
+    /// Whether timecodes are supported at this frame rate.
+    pub fn check_rate(rate: AVRational) -> bool {
+        unsafe { av_timecode_check_frame_rate(rate) == 0 }
+    }
+
+    /// Converts a frame number counted at 30000/1001 or a multiple of it to
+    /// one with the drop-frame numbers skipped.
+    pub fn adjust_drop_frame(frame: i32, fps: u32) -> i32 {
+        unsafe { av_timecode_adjust_ntsc_framenum2(frame, fps as c_int) }
+    }
+
+    pub fn as_ptr(&self) -> *const AVTimecode {
+        &self.tc
+    }
+
+    pub fn rate(&self) -> AVRational {
+        self.tc.rate
+    }
+
+    /// The frame rate rounded to an integer, i.e. frames per timecode second.
+    pub fn fps(&self) -> u32 {
+        self.tc.fps as u32
+    }
+
+    pub fn flags(&self) -> TimecodeFlags {
+        TimecodeFlags::from_bits_truncate(self.tc.flags)
+    }
+
+    pub fn is_drop_frame(&self) -> bool {
+        self.flags().contains(TimecodeFlags::DROP_FRAME)
+    }
+
+    /// The frame number the timecode starts at.
+    pub fn start(&self) -> i32 {
+        self.tc.start
+    }
+
+    /// The timecode `frame` frames after the start.
+    pub fn to_string_at(&self, frame: i32) -> String {
+        let mut buf = [0 as c_char; AV_TIMECODE_STR_SIZE as usize];
+
+        unsafe {
+            av_timecode_make_string(&self.tc, buf.as_mut_ptr(), frame);
+            CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned()
+        }
+    }
+
+    /// The SMPTE 12M binary timecode `frame` frames after the start.
+    pub fn smpte_at(&self, frame: i32) -> SmpteTimecode {
+        SmpteTimecode::from_packed(unsafe { av_timecode_get_smpte_from_framenum(&self.tc, frame) })
+    }
+
+    /// Formats a 25-bit timecode from an MPEG GOP header.
+    pub fn mpeg_to_string(value: u32) -> String {
+        let mut buf = [0 as c_char; AV_TIMECODE_STR_SIZE as usize];
+
+        unsafe {
+            av_timecode_make_mpeg_tc_string(buf.as_mut_ptr(), value);
+            CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned()
+        }
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string_at(0))
+    }
+}
+
+/// The fields of a SMPTE 12M timecode as packed in 32 bits of binary coded
+/// decimal, e.g. in MXF or SEI messages.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct SmpteTimecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub drop_frame: bool,
+}
+
+impl SmpteTimecode {
+    /// Fields that are not valid BCD read as 0. The binary groups and the
+    /// flag bits besides drop frame are ignored.
+    pub fn from_packed(value: u32) -> Self {
+        SmpteTimecode {
+            hours: bcd_to_u8(value & 0x3f),
+            minutes: bcd_to_u8(value >> 8 & 0x7f),
+            seconds: bcd_to_u8(value >> 16 & 0x7f),
+            frames: bcd_to_u8(value >> 24 & 0x3f),
+            drop_frame: value & 1 << 30 != 0,
+        }
+    }
+
+    pub fn to_packed(&self) -> u32 {
+        (self.drop_frame as u32) << 30
+            | u8_to_bcd(self.frames) << 24
+            | u8_to_bcd(self.seconds) << 16
+            | u8_to_bcd(self.minutes) << 8
+            | u8_to_bcd(self.hours)
+    }
+}
+
+impl fmt::Display for SmpteTimecode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours,
+            self.minutes,
+            self.seconds,
+            if self.drop_frame { ';' } else { ':' },
+            self.frames
+        )
+    }
+}
+
+fn bcd_to_u8(value: u32) -> u8 {
+    let (high, low) = (value >> 4, value & 0xf);
+
+    if high > 9 || low > 9 {
+        0
+    } else {
+        (high * 10 + low) as u8
+    }
+}
+
+fn u8_to_bcd(value: u8) -> u32 {
+    u32::from(value / 10 % 10) << 4 | u32::from(value % 10)
+}
@@ -1,8 +1,23 @@
-use libc::{c_int, int64_t};
-use {AVRational, AV_TIME_BASE};
+use std::ffi::{CStr, CString};
+
+use libc::{c_char, c_int, int64_t, EINVAL};
+use {AVRational, AvError, AVERROR, AV_TIME_BASE};
 
 pub const AV_NOPTS_VALUE: int64_t = 0x8000000000000000u64 as int64_t;
 pub const AV_TIME_BASE_Q: AVRational = AVRational {
     num: 1,
     den: AV_TIME_BASE as c_int,
 };
+
+pub(crate) fn cstring(value: &str) -> Result<CString, AvError> {
+    CString::new(value).map_err(|_| AvError(AVERROR(EINVAL)))
+}
+
+/// Borrows a nul terminated C string, `None` if null or not valid UTF-8.
+pub(crate) unsafe fn ptr_to_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+}
@@ -3,9 +3,24 @@
 #![allow(non_snake_case)]
 
 extern crate libc;
+#[macro_use]
+extern crate bitflags;
+#[cfg(feature = "log")]
+#[macro_use(log)]
+extern crate log;
+#[cfg(feature = "tracing")]
+#[macro_use(event)]
+extern crate tracing;
+#[cfg(feature = "rand_core")]
+extern crate rand_core;
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
 #[macro_use]
 mod avutil;
 pub use avutil::*;
+
+#[cfg(feature = "avcodec")]
+mod avcodec;
+#[cfg(feature = "avcodec")]
+pub use avcodec::*;